@@ -0,0 +1,262 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Map of monitor alias or id to connected monitor.
+pub(crate) fn monitor_map<'a>(
+    connection: &'a crate::connection::Connection,
+    config: &crate::config::Config,
+) -> Result<
+    std::collections::HashMap<String, crate::monitor::Monitor<'a>>,
+    crate::errors::X11EDIDAutoError,
+> {
+    let mut monitor_map: std::collections::HashMap<String, crate::monitor::Monitor> =
+        std::collections::HashMap::new();
+
+    // Outputs to monitors.
+    println!("Getting monitors...");
+    // Loop outputs.
+    for output in connection.outputs()? {
+        // Create monitor for output.
+        let monitor: crate::monitor::Monitor = crate::monitor::Monitor::new(connection, output)?;
+        // Get its alias, if the alias is not already taken.
+        let alias: Option<String> = config
+            .alias(&monitor)?
+            .filter(|alias| !monitor_map.contains_key(alias));
+        // If monitor has EDID or alias
+        if monitor.has_edid() || alias.is_some() {
+            let monitor: crate::monitor::Monitor = monitor.with_alias(alias);
+            // and its output is connected,
+            if monitor.is_connected(config.include_unknown)? {
+                // add it to map.
+                monitor_map.insert(monitor.key(), monitor);
+            }
+            // otherwise inform user about skipping it.
+            else if monitor.has_edid() {
+                println!("Skipping monitor that is not connected:");
+                for info in monitor.monitor_info() {
+                    println!("\t {}", info);
+                }
+            }
+        }
+    }
+
+    // If no monitors found
+    if monitor_map.is_empty() {
+        // return with error.
+        return Err(crate::errors::X11EDIDAutoError::NoMonitorsFoundError(
+            crate::errors::NoMonitorsFoundError::new(),
+        ));
+    }
+
+    Ok(monitor_map)
+}
+
+// Monitors that are not connected, but still have CRTC, so they have to be disabled.
+pub(crate) fn disconnected<'a>(
+    connection: &'a crate::connection::Connection,
+    config: &crate::config::Config,
+    monitor_map: &std::collections::HashMap<String, crate::monitor::Monitor>,
+) -> Result<Vec<crate::monitor::Monitor<'a>>, crate::errors::X11EDIDAutoError> {
+    let mut disconnected: Vec<crate::monitor::Monitor> = Vec::new();
+    for output in connection.outputs()? {
+        if monitor_map
+            .values()
+            .any(|monitor| monitor.output() == output)
+        {
+            continue;
+        }
+        let monitor: crate::monitor::Monitor = crate::monitor::Monitor::new(connection, output)?;
+        if !monitor.is_connected(config.include_unknown)? && monitor.crtc()? != 0 {
+            disconnected.push(monitor);
+        }
+    }
+    Ok(disconnected)
+}
+
+// Select monitor group for connected monitors.
+pub(crate) fn select<'c>(
+    config: &'c crate::config::Config,
+    monitor_map: &std::collections::HashMap<String, crate::monitor::Monitor>,
+    command: &crate::arguments::Command,
+    profile: Option<&str>,
+) -> Result<(usize, &'c crate::config::MonitorGroup), crate::errors::X11EDIDAutoError> {
+    // Monitor groups that match connected monitors.
+    let matching: Vec<(usize, &crate::config::MonitorGroup)> =
+        crate::selection::matching(config, monitor_map);
+
+    // Select given profile, next one when cycling or the most preferred matching monitor group.
+    let selected_monitor_group: Option<(usize, &crate::config::MonitorGroup)> =
+        match (command, profile) {
+            (_, Some(name)) => Some(crate::selection::profile(config, name, monitor_map)?),
+            (crate::arguments::Command::Cycle, None) => {
+                crate::selection::next(&matching, crate::state::read_last_profile().as_deref())
+            }
+            (_, None) => matching.first().copied(),
+        };
+
+    // If did not find any monitor group return error.
+    selected_monitor_group.ok_or_else(|| {
+        crate::errors::X11EDIDAutoError::NoMonitorGroupWithAllMonitorsPresentError(
+            crate::errors::NoMonitorGroupWithAllMonitorsPresentError::new(
+                monitor_map
+                    .values()
+                    .map(|monitor| monitor.monitor_info())
+                    .collect(),
+                monitor_map
+                    .values()
+                    .filter(|monitor| !monitor.has_alias())
+                    .map(|monitor| (monitor.name().unwrap_or_default(), monitor.monitor_id()))
+                    .collect(),
+            ),
+        )
+    })
+}
+
+// Run hook when selecting failed because no monitor group matched, unless on dry run.
+pub(crate) fn no_match(
+    config: &crate::config::Config,
+    monitor_map: &std::collections::HashMap<String, crate::monitor::Monitor>,
+    error: &crate::errors::X11EDIDAutoError,
+    dry_run: bool,
+) {
+    if let crate::errors::X11EDIDAutoError::NoMonitorGroupWithAllMonitorsPresentError(error) = error
+    {
+        if !dry_run {
+            let mut environment: Vec<(String, String)> = environment(monitor_map);
+            environment.push(("X11_EDID_AUTO_ERROR".to_string(), error.summary()));
+            crate::hooks::run(config, None, crate::hooks::Hook::OnNoMatch, &environment);
+        }
+    }
+}
+
+// Environment for hooks with connected monitors mapped to their outputs.
+fn environment(
+    monitor_map: &std::collections::HashMap<String, crate::monitor::Monitor>,
+) -> Vec<(String, String)> {
+    let mut monitors: Vec<String> = monitor_map
+        .iter()
+        .map(|(key, monitor)| format!("{}={}", key, monitor.name().unwrap_or_default()))
+        .collect();
+    monitors.sort();
+    vec![("X11_EDID_AUTO_MONITORS".to_string(), monitors.join(" "))]
+}
+
+// Apply monitor group for connected monitors, running hook if it failed.
+pub(crate) fn apply(
+    connection: &crate::connection::Connection,
+    config: &crate::config::Config,
+    monitor_map: &std::collections::HashMap<String, crate::monitor::Monitor>,
+    monitor_group: (usize, &crate::config::MonitorGroup),
+    dry_run: bool,
+) -> Result<(), crate::errors::X11EDIDAutoError> {
+    let result: Result<(), crate::errors::X11EDIDAutoError> =
+        plan_and_apply(connection, config, monitor_map, monitor_group, dry_run);
+    if let Err(error) = &result {
+        if !dry_run {
+            let mut environment: Vec<(String, String)> = environment(monitor_map);
+            environment.push(("X11_EDID_AUTO_ERROR".to_string(), error.to_string()));
+            crate::hooks::run(
+                config,
+                Some(monitor_group),
+                crate::hooks::Hook::OnError,
+                &environment,
+            );
+        }
+    }
+    result
+}
+
+// Plan monitor group and apply it.
+fn plan_and_apply(
+    connection: &crate::connection::Connection,
+    config: &crate::config::Config,
+    monitor_map: &std::collections::HashMap<String, crate::monitor::Monitor>,
+    (monitor_group_index, monitor_group): (usize, &crate::config::MonitorGroup),
+    dry_run: bool,
+) -> Result<(), crate::errors::X11EDIDAutoError> {
+    // Plan monitor group, disabling monitors that were disconnected.
+    let disconnected: Vec<crate::monitor::Monitor> = disconnected(connection, config, monitor_map)?;
+    let plan: crate::plan::Plan = crate::plan::Plan::new(
+        connection,
+        monitor_group,
+        monitor_group
+            .unlisted
+            .or(config.unlisted)
+            .unwrap_or_default(),
+        monitor_map,
+        &disconnected,
+    )?;
+    plan.print();
+
+    // DPI for the screen, from config or primary monitor.
+    let dpi: Option<f64> = match config
+        .resources
+        .as_ref()
+        .and_then(|resources| resources.dpi)
+    {
+        Some(crate::config::ResourcesDpi::Dpi(dpi)) => Some(dpi),
+        _ => plan.dpi(),
+    };
+
+    // X resources to set.
+    let mut resources: Vec<(&str, String)> = Vec::new();
+    if let Some(config_resources) = &config.resources {
+        if let (Some(_), Some(dpi)) = (config_resources.dpi, dpi) {
+            resources.push(("Xft.dpi", format!("{}", dpi.round() as u32)));
+        }
+        if let Some(cursor_size) = config_resources.cursor_size {
+            resources.push(("Xcursor.size", cursor_size.to_string()));
+        }
+    }
+
+    // If only dry run
+    if dry_run {
+        // do not apply anything.
+        for (name, value) in &resources {
+            println!("Resource {}: {}", name, value);
+        }
+        println!("Dry run, not applying plan!");
+        return Ok(());
+    }
+
+    // Run hook before applying.
+    let mut environment: Vec<(String, String)> = environment(monitor_map);
+    environment.extend(plan.environment()?);
+    crate::hooks::run(
+        config,
+        Some((monitor_group_index, monitor_group)),
+        crate::hooks::Hook::PreApply,
+        &environment,
+    );
+
+    // Apply plan and end connection.
+    plan.apply(dpi)?;
+    connection.end()?;
+
+    // Set X resources.
+    if !resources.is_empty() {
+        connection.set_resources(&resources)?;
+    }
+
+    // Remember applied monitor group for cycling, monitors are applied even if this fails.
+    if let Err(error) = crate::state::write_last_profile(&monitor_group.key(monitor_group_index)) {
+        crate::log::error(&error);
+    }
+
+    // Tell user about applied monitor group.
+    let mut monitors: Vec<&str> = monitor_map.keys().map(String::as_str).collect();
+    monitors.sort();
+    crate::notification::applied(config, &monitor_group.label(monitor_group_index), &monitors);
+
+    // Run hook after applying.
+    crate::hooks::run(
+        config,
+        Some((monitor_group_index, monitor_group)),
+        crate::hooks::Hook::PostApply,
+        &environment,
+    );
+
+    // Done.
+    println!("Done!");
+    Ok(())
+}
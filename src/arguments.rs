@@ -0,0 +1,142 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Commands.
+pub(crate) enum Command {
+    // Apply selected monitor group.
+    Apply,
+    // Apply next matching monitor group after the last applied one.
+    Cycle,
+    // Apply selected monitor group whenever connected monitors change.
+    Watch,
+    // Show status of watch mode.
+    Status,
+    // Reload config of watch mode.
+    Reload,
+    // Pause automatic applying of watch mode.
+    Pause,
+    // Resume automatic applying of watch mode.
+    Resume,
+    // Write systemd user unit for watch mode.
+    InstallService,
+}
+
+// Methods for commands.
+impl Command {
+    // Is command only for controlling watch mode?
+    pub(crate) fn is_control(&self) -> bool {
+        matches!(
+            self,
+            Command::Status | Command::Reload | Command::Pause | Command::Resume
+        )
+    }
+}
+
+// Structure for command line arguments.
+pub(crate) struct Arguments {
+    // Command to run.
+    pub(crate) command: Command,
+    // Path to config file.
+    pub(crate) config_file_path: String,
+    // Only print plan without applying it.
+    pub(crate) dry_run: bool,
+    // Profile to use instead of automatic selection.
+    pub(crate) profile: Option<String>,
+    // Only list matching profiles.
+    pub(crate) list_profiles: bool,
+    // Seconds to wait for the most preferred monitor group.
+    pub(crate) wait: Option<f64>,
+    // Run watch mode as systemd service.
+    pub(crate) systemd: bool,
+}
+
+// Methods for arguments.
+impl Arguments {
+    // Parse arguments from command line.
+    pub(crate) fn new() -> Result<Self, crate::errors::InvalidArgumentsError> {
+        let mut args = std::env::args();
+        let script: String = args.next().unwrap_or_default();
+
+        let mut args = args.peekable();
+
+        // Apply is the default command.
+        let command: Command = match args.peek().map(String::as_str) {
+            Some("apply") => {
+                args.next();
+                Command::Apply
+            }
+            Some("cycle") => {
+                args.next();
+                Command::Cycle
+            }
+            Some("watch") => {
+                args.next();
+                Command::Watch
+            }
+            Some("status") => {
+                args.next();
+                Command::Status
+            }
+            Some("reload") => {
+                args.next();
+                Command::Reload
+            }
+            Some("pause") => {
+                args.next();
+                Command::Pause
+            }
+            Some("resume") => {
+                args.next();
+                Command::Resume
+            }
+            Some("install-service") => {
+                args.next();
+                Command::InstallService
+            }
+            _ => Command::Apply,
+        };
+
+        let mut config_file_path: Option<String> = None;
+        let mut dry_run: bool = false;
+        let mut profile: Option<String> = None;
+        let mut list_profiles: bool = false;
+        let mut wait: Option<f64> = None;
+        let mut systemd: bool = false;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--dry-run" => dry_run = true,
+                "--list-profiles" => list_profiles = true,
+                "--systemd" => systemd = true,
+                "--profile" => match args.next() {
+                    Some(name) => profile = Some(name),
+                    None => return Err(crate::errors::InvalidArgumentsError::new(script)),
+                },
+                "--wait" => match args.next().and_then(|secs| secs.parse::<f64>().ok()) {
+                    Some(secs) if secs >= 0.0 => wait = Some(secs),
+                    _ => return Err(crate::errors::InvalidArgumentsError::new(script)),
+                },
+                _ if config_file_path.is_none() && !arg.starts_with("--") => {
+                    config_file_path = Some(arg.trim().to_string())
+                }
+                _ => return Err(crate::errors::InvalidArgumentsError::new(script)),
+            }
+        }
+
+        // Config file path is required, except for controlling watch mode.
+        if command.is_control() {
+            config_file_path = Some(config_file_path.unwrap_or_default());
+        }
+        match config_file_path {
+            Some(config_file_path) => Ok(Arguments {
+                command,
+                config_file_path,
+                dry_run,
+                profile,
+                list_profiles,
+                wait,
+                systemd,
+            }),
+            None => Err(crate::errors::InvalidArgumentsError::new(script)),
+        }
+    }
+}
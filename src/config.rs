@@ -0,0 +1,1145 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Configuration.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    #[serde(default)]
+    pub(crate) monitor_groups: Vec<MonitorGroup>,
+    // Named monitor groups.
+    #[serde(default)]
+    pub(crate) profiles: Vec<MonitorGroup>,
+    // Use outputs with unknown connection status.
+    #[serde(default)]
+    pub(crate) include_unknown: bool,
+    // Aliases for monitors.
+    #[serde(default)]
+    pub(crate) monitors: std::collections::BTreeMap<String, MonitorCriteria>,
+    // How to select monitor group.
+    #[serde(default)]
+    pub(crate) selection: Selection,
+    // Policy for connected monitors that are not in monitor group.
+    pub(crate) unlisted: Option<Unlisted>,
+    // X resources to set after applying monitor group.
+    pub(crate) resources: Option<Resources>,
+    // Options for watch mode.
+    #[serde(default)]
+    pub(crate) watch: Watch,
+    // What to do when another instance is running.
+    #[serde(default)]
+    pub(crate) lock: Lock,
+    // Send desktop notifications.
+    #[serde(default)]
+    pub(crate) notifications: bool,
+    // Commands to run around applying.
+    #[serde(default)]
+    pub(crate) hooks: Hooks,
+}
+
+// Methods for configuration.
+impl Config {
+    // Load config from file.
+    pub(crate) fn load(config_file_path: &str) -> Result<Self, crate::errors::ConfigLoadError> {
+        Self::parse(
+            &std::fs::read_to_string(config_file_path)?,
+            config_file_path,
+        )
+    }
+
+    // Parse config from contents of file.
+    fn parse(
+        contents: &str,
+        config_file_path: &str,
+    ) -> Result<Self, crate::errors::ConfigLoadError> {
+        // Generate config from file.
+        let mut config: Config = toml::from_str(contents)?;
+        config.resolve_aliases();
+
+        // If no monitor groups given
+        if config.monitor_groups().next().is_none() {
+            // return with error.
+            return Err(crate::errors::ConfigLoadError::NoMonitorGroupsGivenError(
+                crate::errors::NoMonitorGroupsGivenError::new(config_file_path.to_string()),
+            ));
+        }
+
+        // Profile names have to be unique and differ from keys of unnamed monitor groups.
+        let mut names: std::collections::HashSet<&str> = std::collections::HashSet::new();
+        for name in config
+            .monitor_groups()
+            .filter_map(|monitor_group| monitor_group.name.as_deref())
+        {
+            if name.starts_with('#') {
+                return Err(crate::errors::ReservedProfileNameError::new(name.to_string()).into());
+            }
+            if !names.insert(name) {
+                return Err(crate::errors::DuplicateProfileNameError::new(name.to_string()).into());
+            }
+        }
+
+        // Mirrored monitors have to be part of their monitor group.
+        for monitor_group in config.monitor_groups() {
+            for monitor_id in &monitor_group.mirror {
+                if !monitor_group.monitors.contains(monitor_id) {
+                    return Err(crate::errors::MirrorMonitorNotInGroupError::new(
+                        monitor_id.to_string(),
+                    )
+                    .into());
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    // Monitor groups followed by profiles, in file order.
+    pub(crate) fn monitor_groups(&self) -> impl Iterator<Item = &MonitorGroup> {
+        self.monitor_groups.iter().chain(self.profiles.iter())
+    }
+
+    // Refer to aliased monitors with aliases instead of their EDID IDs,
+    // when all of the monitors with EDID ID get the same alias.
+    fn resolve_aliases(&mut self) {
+        let aliases: std::collections::HashMap<String, Option<String>> = self
+            .monitors
+            .values()
+            .filter_map(|criteria| criteria.edid.clone())
+            .map(|edid| {
+                let alias: Option<String> = self.edid_alias(&edid).cloned();
+                (edid, alias)
+            })
+            .collect();
+        for monitor_group in self
+            .monitor_groups
+            .iter_mut()
+            .chain(self.profiles.iter_mut())
+        {
+            for monitor_id in monitor_group
+                .monitors
+                .iter_mut()
+                .chain(monitor_group.optional.iter_mut())
+                .chain(monitor_group.mirror.iter_mut())
+            {
+                match aliases.get(monitor_id) {
+                    Some(Some(alias)) => *monitor_id = alias.clone(),
+                    Some(None) => eprintln!(
+                        "Monitor {} is not referred to with alias, since its alias depends on other criteria than EDID ID!",
+                        monitor_id
+                    ),
+                    None => {}
+                }
+            }
+        }
+    }
+
+    // Alias that every monitor with EDID ID gets, by the rules of alias.
+    fn edid_alias(&self, edid: &str) -> Option<&String> {
+        // First of the most specific aliases that monitor with EDID ID could get
+        let (alias, criteria): (&String, &MonitorCriteria) =
+            self.monitors.iter().find(|(_, criteria)| {
+                criteria.specificity() == 2
+                    && criteria.edid.as_deref().map_or(true, |own| own == edid)
+            })?;
+        // is its alias, if it matches all of them.
+        (criteria.only_edid() == Some(edid)).then_some(alias)
+    }
+
+    // Alias for monitor, the one with most specific criteria it matches,
+    // alphabetically first of equally specific ones.
+    pub(crate) fn alias(
+        &self,
+        monitor: &crate::monitor::Monitor,
+    ) -> Result<Option<String>, crate::errors::MonitorNameError> {
+        self.alias_matching(|criteria| monitor.matches(criteria))
+    }
+
+    // Alias with the most specific criteria that match.
+    fn alias_matching<E>(
+        &self,
+        mut matches: impl FnMut(&MonitorCriteria) -> Result<bool, E>,
+    ) -> Result<Option<String>, E> {
+        let mut best: Option<(u8, &String)> = None;
+        for (alias, criteria) in &self.monitors {
+            if best.map_or(true, |(specificity, _)| {
+                criteria.specificity() > specificity
+            }) && matches(criteria)?
+            {
+                best = Some((criteria.specificity(), alias));
+            }
+        }
+        Ok(best.map(|(_, alias)| alias.clone()))
+    }
+
+    // Monitor group with given name.
+    pub(crate) fn profile(&self, name: &str) -> Option<(usize, &MonitorGroup)> {
+        self.monitor_groups()
+            .enumerate()
+            .find(|(_, monitor_group)| monitor_group.name.as_deref() == Some(name))
+    }
+}
+
+// Label for messages from key of monitor group.
+pub(crate) fn label(key: &str) -> String {
+    match key.strip_prefix('#') {
+        Some(number) => format!("{}. monitor group", number),
+        None => format!("{:?} profile", key),
+    }
+}
+
+// Monitor criteria as it is written in config file, EDID ID or table.
+type MonitorCriteriaConfig = ShortOrTable<String, MonitorCriteriaTableConfig>;
+
+// Monitor criteria as table.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MonitorCriteriaTableConfig {
+    edid: Option<String>,
+    fingerprint: Option<String>,
+    name: Option<String>,
+    connector: Option<String>,
+    connector_type: Option<String>,
+    internal: Option<bool>,
+}
+
+// Criteria for matching monitor, all given ones have to match.
+#[derive(Debug, serde::Deserialize)]
+#[serde(from = "MonitorCriteriaConfig")]
+pub(crate) struct MonitorCriteria {
+    // EDID ID.
+    pub(crate) edid: Option<String>,
+    // Fingerprint of the whole EDID.
+    pub(crate) fingerprint: Option<String>,
+    // Name from EDID display name descriptor.
+    pub(crate) name: Option<String>,
+    // Output name, with * and ? wildcards.
+    pub(crate) connector: Option<String>,
+    // Connector type of output, like Panel, DisplayPort, HDMI or VGA.
+    pub(crate) connector_type: Option<String>,
+    // Is internal laptop panel?
+    pub(crate) internal: Option<bool>,
+}
+impl MonitorCriteria {
+    // Are there no criteria?
+    pub(crate) fn is_empty(&self) -> bool {
+        self.edid.is_none()
+            && self.fingerprint.is_none()
+            && self.name.is_none()
+            && self.connector.is_none()
+            && self.connector_type.is_none()
+            && self.internal.is_none()
+    }
+
+    // EDID ID, when it is the only criterion.
+    fn only_edid(&self) -> Option<&str> {
+        if self.fingerprint.is_none()
+            && self.name.is_none()
+            && self.connector.is_none()
+            && self.connector_type.is_none()
+            && self.internal.is_none()
+        {
+            self.edid.as_deref()
+        } else {
+            None
+        }
+    }
+
+    // How specific criteria are: EDID or fingerprint, then name, then output based ones.
+    pub(crate) fn specificity(&self) -> u8 {
+        if self.edid.is_some() || self.fingerprint.is_some() {
+            2
+        } else if self.name.is_some() {
+            1
+        } else {
+            0
+        }
+    }
+}
+impl From<MonitorCriteriaConfig> for MonitorCriteria {
+    fn from(monitor_criteria_config: MonitorCriteriaConfig) -> Self {
+        match monitor_criteria_config {
+            ShortOrTable::Short(edid) => MonitorCriteria {
+                edid: Some(edid),
+                fingerprint: None,
+                name: None,
+                connector: None,
+                connector_type: None,
+                internal: None,
+            },
+            ShortOrTable::Table(table) => MonitorCriteria {
+                edid: table.edid,
+                fingerprint: table.fingerprint,
+                name: table.name,
+                connector: table.connector,
+                connector_type: table.connector_type,
+                internal: table.internal,
+            },
+        }
+    }
+}
+
+// What to do when another instance is running.
+#[derive(Debug, Default, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Lock {
+    // Wait for it to finish.
+    #[default]
+    Wait,
+    // Exit with error.
+    Exit,
+}
+
+// Options for watch mode.
+#[derive(Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Watch {
+    // Seconds without changes before acting on them.
+    pub(crate) settle: f64,
+    // Maximum number of applies in a minute, 0 for no limit.
+    pub(crate) max_applies_per_minute: usize,
+}
+impl Default for Watch {
+    fn default() -> Self {
+        Watch {
+            settle: 1.0,
+            max_applies_per_minute: 6,
+        }
+    }
+}
+
+// Commands to run around applying.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Hooks {
+    // Before applying monitor group.
+    pub(crate) pre_apply: Option<String>,
+    // After monitor group was applied.
+    pub(crate) post_apply: Option<String>,
+    // When no monitor group matches connected monitors.
+    pub(crate) on_no_match: Option<String>,
+    // When applying monitor group failed.
+    pub(crate) on_error: Option<String>,
+    // Seconds before command is killed.
+    pub(crate) timeout: Option<f64>,
+}
+
+// X resources to set after applying monitor group.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Resources {
+    // DPI for Xft.dpi and screen's physical size.
+    pub(crate) dpi: Option<ResourcesDpi>,
+    // Xcursor.size.
+    pub(crate) cursor_size: Option<u32>,
+}
+
+// DPI for X resources.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ResourcesDpi {
+    // Get DPI with given mode.
+    Mode(ResourcesDpiMode),
+    // Given DPI.
+    Dpi(f64),
+}
+impl<'de> serde::Deserialize<'de> for ResourcesDpi {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ResourcesDpiVisitor)
+    }
+}
+
+// Visitor for DPI for X resources, so that unknown mode is named in error.
+struct ResourcesDpiVisitor;
+impl<'de> serde::de::Visitor<'de> for ResourcesDpiVisitor {
+    type Value = ResourcesDpi;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a DPI mode or a DPI")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        serde::Deserialize::deserialize(serde::de::IntoDeserializer::into_deserializer(value))
+            .map(ResourcesDpi::Mode)
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(ResourcesDpi::Dpi(value))
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(ResourcesDpi::Dpi(value as f64))
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(ResourcesDpi::Dpi(value as f64))
+    }
+}
+
+// Modes for getting DPI for X resources.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ResourcesDpiMode {
+    // DPI of the primary monitor.
+    Primary,
+}
+
+// Automatic scaling of monitors to uniform DPI.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum AutoScale {
+    // Match DPI of given mode.
+    Mode(AutoScaleMode),
+    // Match given target DPI.
+    Dpi(f64),
+}
+impl<'de> serde::Deserialize<'de> for AutoScale {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(AutoScaleVisitor)
+    }
+}
+
+// Visitor for automatic scaling, so that unknown mode is named in error.
+struct AutoScaleVisitor;
+impl<'de> serde::de::Visitor<'de> for AutoScaleVisitor {
+    type Value = AutoScale;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a scaling mode or a target DPI")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        serde::Deserialize::deserialize(serde::de::IntoDeserializer::into_deserializer(value))
+            .map(AutoScale::Mode)
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(AutoScale::Dpi(value))
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(AutoScale::Dpi(value as f64))
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(AutoScale::Dpi(value as f64))
+    }
+}
+
+// Modes for automatic scaling.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AutoScaleMode {
+    // Match DPI of the monitor with lowest DPI.
+    MatchLowest,
+    // Match DPI of the primary monitor.
+    MatchPrimary,
+}
+
+// Ways to select monitor group.
+#[derive(Debug, Default, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Selection {
+    // First matching group in file order.
+    #[default]
+    First,
+    // Matching group that covers the most monitors, then with highest priority, then first in file order.
+    Score,
+}
+
+// Policies for connected monitors that are not in monitor group.
+#[derive(Debug, Default, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Unlisted {
+    // Disable them.
+    #[default]
+    Disable,
+    // Keep them as they are.
+    Keep,
+    // Extend them right of the monitor group.
+    ExtendRight,
+    // Mirror the primary monitor on them.
+    MirrorPrimary,
+}
+
+// Value in config file, that is either in short form or a table.
+// Tables are deserialized on their own, so that errors name the bad field.
+enum ShortOrTable<S, T> {
+    // Short form, like string or list.
+    Short(S),
+    // Table with options.
+    Table(T),
+}
+impl<'de, S: serde::Deserialize<'de>, T: serde::Deserialize<'de>> serde::Deserialize<'de>
+    for ShortOrTable<S, T>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ShortOrTableVisitor(std::marker::PhantomData))
+    }
+}
+
+// Visitor for value that is either in short form or a table.
+struct ShortOrTableVisitor<S, T>(std::marker::PhantomData<(S, T)>);
+impl<'de, S: serde::Deserialize<'de>, T: serde::Deserialize<'de>> serde::de::Visitor<'de>
+    for ShortOrTableVisitor<S, T>
+{
+    type Value = ShortOrTable<S, T>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a string, a list or a table")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        S::deserialize(serde::de::IntoDeserializer::into_deserializer(value))
+            .map(ShortOrTable::Short)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        S::deserialize(serde::de::value::SeqAccessDeserializer::new(seq)).map(ShortOrTable::Short)
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        T::deserialize(serde::de::value::MapAccessDeserializer::new(map)).map(ShortOrTable::Table)
+    }
+}
+
+// Monitor of group as it is written in config file, monitor ID or table.
+type MonitorConfig = ShortOrTable<String, MonitorTableConfig>;
+
+// Monitor of group as table with monitor ID and options.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MonitorTableConfig {
+    id: String,
+    #[serde(default)]
+    optional: bool,
+}
+
+// Monitor group as it is written in config file, list of monitors or table.
+type MonitorGroupConfig = ShortOrTable<Vec<MonitorConfig>, MonitorGroupTableConfig>;
+
+// Monitor group as table with monitors and options.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MonitorGroupTableConfig {
+    name: Option<String>,
+    description: Option<String>,
+    monitors: Vec<MonitorConfig>,
+    auto_scale: Option<AutoScale>,
+    #[serde(default)]
+    mirror: Vec<String>,
+    #[serde(default)]
+    mirror_scale: bool,
+    unlisted: Option<Unlisted>,
+    #[serde(default)]
+    priority: i64,
+    #[serde(default)]
+    exact: bool,
+    #[serde(default)]
+    hooks: Hooks,
+}
+
+// Monitor group.
+#[derive(Debug, serde::Deserialize)]
+#[serde(from = "MonitorGroupConfig")]
+pub(crate) struct MonitorGroup {
+    // Name of profile.
+    pub(crate) name: Option<String>,
+    // Description of profile.
+    pub(crate) description: Option<String>,
+    // Monitor aliases or IDs from left to right, first present one is primary.
+    pub(crate) monitors: Vec<String>,
+    // Monitor IDs that do not have to be present.
+    pub(crate) optional: Vec<String>,
+    // Automatic scaling to uniform DPI.
+    pub(crate) auto_scale: Option<AutoScale>,
+    // Monitor IDs that show the same content, at position of first of them.
+    pub(crate) mirror: Vec<String>,
+    // Scale mirrors to fit the content, when they do not have common resolution.
+    pub(crate) mirror_scale: bool,
+    // Policy for connected monitors that are not in monitor group.
+    pub(crate) unlisted: Option<Unlisted>,
+    // Priority for score selection, higher is preferred.
+    pub(crate) priority: i64,
+    // Match only when connected monitors are exactly the group's monitors.
+    pub(crate) exact: bool,
+    // Commands to run around applying, after the ones from config.
+    pub(crate) hooks: Hooks,
+}
+impl MonitorGroup {
+    // Monitor IDs and optional monitor IDs from monitors in config file.
+    fn split_monitors(monitor_configs: Vec<MonitorConfig>) -> (Vec<String>, Vec<String>) {
+        let mut monitors: Vec<String> = Vec::new();
+        let mut optional: Vec<String> = Vec::new();
+        for monitor_config in monitor_configs {
+            match monitor_config {
+                ShortOrTable::Short(id) => monitors.push(id),
+                ShortOrTable::Table(table) => {
+                    if table.optional {
+                        optional.push(table.id.clone());
+                    }
+                    monitors.push(table.id);
+                }
+            }
+        }
+        (monitors, optional)
+    }
+
+    // Label for messages, name of profile or index of monitor group.
+    pub(crate) fn label(&self, index: usize) -> String {
+        label(&self.key(index))
+    }
+
+    // Key for state file, name of profile or index of monitor group.
+    pub(crate) fn key(&self, index: usize) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("#{}", index + 1),
+        }
+    }
+
+    // Required monitor IDs.
+    pub(crate) fn required(&self) -> impl Iterator<Item = &String> {
+        self.monitors
+            .iter()
+            .filter(|monitor_id| !self.optional.contains(monitor_id))
+    }
+}
+impl From<MonitorGroupConfig> for MonitorGroup {
+    fn from(monitor_group_config: MonitorGroupConfig) -> Self {
+        match monitor_group_config {
+            ShortOrTable::Short(monitor_configs) => {
+                let (monitors, optional): (Vec<String>, Vec<String>) =
+                    Self::split_monitors(monitor_configs);
+                MonitorGroup {
+                    name: None,
+                    description: None,
+                    monitors,
+                    optional,
+                    auto_scale: None,
+                    mirror: Vec::new(),
+                    mirror_scale: false,
+                    unlisted: None,
+                    priority: 0,
+                    exact: false,
+                    hooks: Hooks::default(),
+                }
+            }
+            ShortOrTable::Table(table) => {
+                let (monitors, optional): (Vec<String>, Vec<String>) =
+                    Self::split_monitors(table.monitors);
+                MonitorGroup {
+                    name: table.name,
+                    description: table.description,
+                    monitors,
+                    optional,
+                    auto_scale: table.auto_scale,
+                    mirror: table.mirror,
+                    mirror_scale: table.mirror_scale,
+                    unlisted: table.unlisted,
+                    priority: table.priority,
+                    exact: table.exact,
+                    hooks: table.hooks,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn auto_scale_is_mode_or_dpi() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [
+    { monitors = ["AAA:0001:00000001"], auto_scale = "match_lowest" },
+    { monitors = ["AAA:0001:00000001"], auto_scale = 96 },
+    { monitors = ["AAA:0001:00000001"], auto_scale = 120.5 },
+]
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        let auto_scales: Vec<String> = config
+            .monitor_groups()
+            .map(|monitor_group| format!("{:?}", monitor_group.auto_scale))
+            .collect();
+        assert_eq!(
+            auto_scales,
+            vec![
+                "Some(Mode(MatchLowest))",
+                "Some(Dpi(96.0))",
+                "Some(Dpi(120.5))"
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_auto_scale_mode_is_named() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [{ monitors = ["AAA:0001:00000001"], auto_scale = "match_highest" }]
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("unknown variant `match_highest`"));
+    }
+
+    #[test]
+    fn misspelled_monitor_group_option_is_error() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [{ monitors = ["AAA:0001:00000001"], autoscale = "match_lowest" }]
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field `autoscale`"));
+    }
+
+    #[test]
+    fn unknown_top_level_keys_are_ignored() {
+        super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001"]]
+comment = "not an option"
+"#,
+            "config.toml",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn resources_dpi_is_mode_or_dpi() {
+        for (dpi, expected) in [
+            ("\"primary\"", "Some(Mode(Primary))"),
+            ("96", "Some(Dpi(96.0))"),
+            ("108.5", "Some(Dpi(108.5))"),
+        ] {
+            let config: super::Config = super::Config::parse(
+                &format!(
+                    "monitor_groups = [[\"AAA:0001:00000001\"]]\n[resources]\ndpi = {}\n",
+                    dpi
+                ),
+                "config.toml",
+            )
+            .unwrap();
+            assert_eq!(format!("{:?}", config.resources.unwrap().dpi), expected);
+        }
+    }
+
+    #[test]
+    fn unknown_resources_dpi_mode_is_named() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001"]]
+
+[resources]
+dpi = "lowest"
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown variant `lowest`"));
+    }
+
+    #[test]
+    fn misspelled_resources_option_is_error() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001"]]
+
+[resources]
+cursor = 32
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field `cursor`"));
+    }
+
+    #[test]
+    fn misspelled_monitor_option_is_error() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001", { id = "BBB:0002:00000002", optinal = true }]]
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field `optinal`"));
+    }
+
+    #[test]
+    fn profile_names_have_to_be_unique() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [{ name = "desk", monitors = ["AAA:0001:00000001"] }]
+
+[[profiles]]
+name = "desk"
+monitors = ["BBB:0002:00000002"]
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            crate::errors::ConfigLoadError::DuplicateProfileNameError(_)
+        ));
+    }
+
+    #[test]
+    fn profile_name_can_not_look_like_group_key() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r##"
+[[profiles]]
+name = "#1"
+monitors = ["AAA:0001:00000001"]
+"##,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            crate::errors::ConfigLoadError::ReservedProfileNameError(_)
+        ));
+    }
+
+    #[test]
+    fn label_is_made_from_key() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001"]]
+profiles = [{ name = "desk", monitors = ["AAA:0001:00000001"] }]
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        let labels: Vec<(String, String)> = config
+            .monitor_groups()
+            .enumerate()
+            .map(|(index, monitor_group)| {
+                (
+                    monitor_group.label(index),
+                    super::label(&monitor_group.key(index)),
+                )
+            })
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                (
+                    "1. monitor group".to_string(),
+                    "1. monitor group".to_string()
+                ),
+                (
+                    "\"desk\" profile".to_string(),
+                    "\"desk\" profile".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn profiles_are_found_by_name_after_monitor_groups() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001"]]
+
+[[profiles]]
+name = "desk"
+monitors = ["AAA:0001:00000001", "BBB:0002:00000002"]
+
+[[profiles]]
+name = "tv"
+monitors = ["CCC:0003:00000003"]
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(config.profile("tv").map(|(index, _)| index), Some(2));
+        assert!(config.profile("#1").is_none());
+    }
+
+    // Monitor IDs of the first monitor group.
+    fn monitor_ids(config: &super::Config) -> Vec<&str> {
+        config
+            .monitor_groups()
+            .next()
+            .unwrap()
+            .monitors
+            .iter()
+            .map(String::as_str)
+            .collect()
+    }
+
+    #[test]
+    fn monitor_criteria_is_edid_or_table() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [["desk"]]
+
+[monitors]
+desk = "AAA:0001:00000001"
+laptop = { name = "Built-in Panel", connector = "eDP*" }
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(
+            config.monitors["desk"].only_edid(),
+            Some("AAA:0001:00000001")
+        );
+        assert_eq!(
+            config.monitors["laptop"].name.as_deref(),
+            Some("Built-in Panel")
+        );
+        assert_eq!(config.monitors["laptop"].connector.as_deref(), Some("eDP*"));
+        assert_eq!(config.monitors["laptop"].specificity(), 1);
+    }
+
+    #[test]
+    fn monitor_criteria_of_wrong_type_is_error() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [["desk"]]
+
+[monitors]
+desk = 5
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("expected a string, a list or a table"));
+    }
+
+    #[test]
+    fn misspelled_monitor_criterion_is_error() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [["desk"]]
+
+[monitors]
+desk = { edi = "AAA:0001:00000001" }
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field `edi`"));
+    }
+
+    #[test]
+    fn most_specific_alias_is_used() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [["desk"]]
+
+[monitors]
+any_hdmi = { connector = "HDMI-*" }
+b_desk = { name = "DELL U2720Q" }
+a_desk = { name = "DELL U2720Q" }
+desk = "AAA:0001:00000001"
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        // Alias for monitor with EDID ID and name on HDMI output.
+        let alias = |edid: &str| {
+            config
+                .alias_matching(|criteria| {
+                    Ok::<bool, ()>(
+                        criteria.edid.as_deref().map_or(true, |own| own == edid)
+                            && criteria
+                                .name
+                                .as_deref()
+                                .map_or(true, |name| name == "DELL U2720Q")
+                            && criteria
+                                .connector
+                                .as_deref()
+                                .map_or(true, |connector| connector == "HDMI-*"),
+                    )
+                })
+                .unwrap()
+        };
+        assert_eq!(alias("AAA:0001:00000001").as_deref(), Some("desk"));
+        assert_eq!(alias("BBB:0002:00000002").as_deref(), Some("a_desk"));
+    }
+
+    #[test]
+    fn output_criteria_are_least_specific() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [["projector"]]
+
+[monitors]
+projector = { connector_type = "HDMI" }
+laptop = { internal = true, connector = "eDP-*" }
+desk = { name = "DELL U2720Q", connector = "DP-*" }
+tv = { fingerprint = "0123456789ABCDEF", connector_type = "HDMI" }
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        let specificities: Vec<(&str, u8)> = config
+            .monitors
+            .iter()
+            .map(|(alias, criteria)| (alias.as_str(), criteria.specificity()))
+            .collect();
+        assert_eq!(
+            specificities,
+            vec![("desk", 1), ("laptop", 0), ("projector", 0), ("tv", 2)]
+        );
+    }
+
+    #[test]
+    fn edid_id_is_replaced_with_its_alias() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001", "BBB:0002:00000002"]]
+
+[monitors]
+desk = "AAA:0001:00000001"
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(monitor_ids(&config), vec!["desk", "BBB:0002:00000002"]);
+    }
+
+    #[test]
+    fn edid_id_is_kept_when_alias_needs_more_criteria() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001"]]
+
+[monitors]
+left = { edid = "AAA:0001:00000001", connector = "DP-1" }
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(monitor_ids(&config), vec!["AAA:0001:00000001"]);
+    }
+
+    #[test]
+    fn edid_id_is_kept_when_alias_is_ambiguous() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001"], ["CCC:0003:00000003"]]
+
+[monitors]
+# Alphabetically first one wins when it matches.
+a_left = { edid = "AAA:0001:00000001", connector = "DP-1" }
+b_desk = "AAA:0001:00000001"
+# Fingerprint may match the same monitor.
+a_projector = { fingerprint = "0123456789ABCDEF" }
+c_tv = "CCC:0003:00000003"
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        let monitor_ids: Vec<&str> = config
+            .monitor_groups()
+            .flat_map(|monitor_group| monitor_group.monitors.iter().map(String::as_str))
+            .collect();
+        assert_eq!(monitor_ids, vec!["AAA:0001:00000001", "CCC:0003:00000003"]);
+    }
+
+    #[test]
+    fn edid_id_is_replaced_with_first_of_equally_specific_aliases() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001"]]
+
+[monitors]
+a_desk = "AAA:0001:00000001"
+b_left = { edid = "AAA:0001:00000001", connector = "DP-1" }
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(monitor_ids(&config), vec!["a_desk"]);
+    }
+
+    #[test]
+    fn watch_options_have_defaults() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001"]]
+
+[watch]
+settle = 0.5
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(config.watch.settle, 0.5);
+        assert_eq!(config.watch.max_applies_per_minute, 6);
+    }
+
+    #[test]
+    fn misspelled_watch_option_is_error() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [["AAA:0001:00000001"]]
+
+[watch]
+settle_time = 0.5
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field `settle_time`"));
+    }
+
+    #[test]
+    fn misspelled_hook_is_error() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [{ monitors = ["AAA:0001:00000001"], hooks = { post_aply = "true" } }]
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field `post_aply`"));
+    }
+
+    #[test]
+    fn mirrored_monitor_has_to_be_in_group() {
+        let error: crate::errors::ConfigLoadError = super::Config::parse(
+            r#"
+monitor_groups = [
+    { monitors = ["AAA:0001:00000001"], mirror = ["AAA:0001:00000001", "BBB:0002:00000002"] },
+]
+"#,
+            "config.toml",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            crate::errors::ConfigLoadError::MirrorMonitorNotInGroupError(_)
+        ));
+    }
+
+    #[test]
+    fn mirrored_alias_is_in_group_by_edid() {
+        let config: super::Config = super::Config::parse(
+            r#"
+monitor_groups = [
+    { monitors = ["laptop", "BBB:0002:00000002"], mirror = ["AAA:0001:00000001", "BBB:0002:00000002"] },
+]
+
+[monitors]
+laptop = "AAA:0001:00000001"
+"#,
+            "config.toml",
+        )
+        .unwrap();
+        assert_eq!(
+            config.monitor_groups().next().unwrap().mirror,
+            vec!["laptop", "BBB:0002:00000002"]
+        );
+    }
+}
@@ -1,6 +1,9 @@
 // Forbid unsafe code.
 #![forbid(unsafe_code)]
 
+// DPI to use for screen's physical size, when it is not known.
+const DEFAULT_DPI: f64 = 96.0;
+
 // Structure for connection.
 pub(crate) struct Connection {
     // Connection to X server.
@@ -48,37 +51,52 @@ impl Connection {
 
     // Generate mode info map.
     fn generate_mode_info_map(mut self) -> Result<Self, x11rb::errors::ReplyError> {
-        // Generate a map of mode info id to mode info.
-        for mode_info in self.screen_resources()?.modes {
-            self.mode_info_map.insert(mode_info.id, mode_info);
-        }
-
+        self.refresh()?;
         Ok(self)
     }
 
+    // Get property with given name for output.
+    fn output_property(
+        &self,
+        output: u32,
+        name: &[u8],
+    ) -> Result<x11rb::protocol::randr::GetOutputPropertyReply, x11rb::errors::ReplyError> {
+        x11rb::protocol::randr::ConnectionExt::randr_get_output_property(
+            &self.connection,
+            output,
+            x11rb::protocol::xproto::ConnectionExt::intern_atom(&self.connection, false, name)?
+                .reply()?
+                .atom,
+            x11rb::protocol::xproto::AtomEnum::ANY,
+            0,
+            u32::MAX,
+            false,
+            false,
+        )?
+        .reply()
+    }
+
     // Get EDID for monitor's output.
     pub(crate) fn edid(&self, output: u32) -> Result<Vec<u8>, x11rb::errors::ReplyError> {
-        Ok(
-            x11rb::protocol::randr::ConnectionExt::randr_get_output_property(
-                &self.connection,
-                output,
-                x11rb::protocol::xproto::ConnectionExt::intern_atom(
-                    &self.connection,
-                    false,
-                    b"EDID",
-                )?
+        Ok(self.output_property(output, b"EDID")?.data.to_vec())
+    }
+
+    // Get connector type for monitor's output, if driver tells it.
+    pub(crate) fn connector_type(
+        &self,
+        output: u32,
+    ) -> Result<Option<String>, x11rb::errors::ReplyError> {
+        // Connector type is an atom.
+        let data: Vec<u8> = self.output_property(output, b"ConnectorType")?.data;
+        let atom: u32 = match data.get(0..4) {
+            Some(bytes) => u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            None => return Ok(None),
+        };
+        let name: Vec<u8> =
+            x11rb::protocol::xproto::ConnectionExt::get_atom_name(&self.connection, atom)?
                 .reply()?
-                .atom,
-                x11rb::protocol::xproto::AtomEnum::ANY,
-                0,
-                u32::MAX,
-                false,
-                false,
-            )?
-            .reply()?
-            .data
-            .to_vec(),
-        )
+                .name;
+        Ok(Some(String::from_utf8_lossy(&name).to_string()))
     }
 
     // Get output info for given output id.
@@ -86,27 +104,30 @@ impl Connection {
         &self,
         output: u32,
     ) -> Result<x11rb::protocol::randr::GetOutputInfoReply, x11rb::errors::ReplyError> {
-        Ok(
-            x11rb::protocol::randr::ConnectionExt::randr_get_output_info(
-                &self.connection,
-                output,
-                0,
-            )?
-            .reply()?,
-        )
+        x11rb::protocol::randr::ConnectionExt::randr_get_output_info(&self.connection, output, 0)?
+            .reply()
     }
 
     // Get CRTC info for monitor's output's CRTC.
-    fn get_crtc_info(
+    pub(crate) fn get_crtc_info(
         &self,
         crtc: u32,
     ) -> Result<x11rb::protocol::randr::GetCrtcInfoReply, x11rb::errors::ReplyError> {
-        Ok(x11rb::protocol::randr::ConnectionExt::randr_get_crtc_info(
+        x11rb::protocol::randr::ConnectionExt::randr_get_crtc_info(
             &self.connection,
             crtc,
             x11rb::CURRENT_TIME,
         )?
-        .reply()?)
+        .reply()
+    }
+
+    // Get CRTC transform.
+    fn get_crtc_transform(
+        &self,
+        crtc: u32,
+    ) -> Result<x11rb::protocol::randr::GetCrtcTransformReply, x11rb::errors::ReplyError> {
+        x11rb::protocol::randr::ConnectionExt::randr_get_crtc_transform(&self.connection, crtc)?
+            .reply()
     }
 
     // Print CRTC info.
@@ -127,30 +148,43 @@ impl Connection {
         &self.mode_info_map
     }
 
+    // Get CRTC config.
+    pub(crate) fn get_crtc_config(
+        &self,
+        crtc: u32,
+    ) -> Result<crate::crtc_config::CrtcConfig, x11rb::errors::ReplyError> {
+        let crtc_info: x11rb::protocol::randr::GetCrtcInfoReply = self.get_crtc_info(crtc)?;
+        let crtc_transform: x11rb::protocol::randr::GetCrtcTransformReply =
+            self.get_crtc_transform(crtc)?;
+        Ok(crate::crtc_config::CrtcConfig {
+            x: crtc_info.x,
+            y: crtc_info.y,
+            mode: crtc_info.mode,
+            rotation: crtc_info.rotation,
+            transform: crtc_transform.current_transform,
+            filter_name: crtc_transform.current_filter_name,
+            filter_params: crtc_transform.current_params,
+            outputs: crtc_info.outputs,
+        })
+    }
+
     // Set CRTC config
     pub(crate) fn set_crtc_config(
         &self,
         crtc: u32,
-        x: i16,
-        y: i16,
-        mode: u32,
-        rotation: x11rb::protocol::randr::Rotation,
-        outputs: &[u32],
+        crtc_config: &crate::crtc_config::CrtcConfig,
         crtc_existing: u32,
-    ) -> Result<&Self, x11rb::errors::ReplyError> {
+    ) -> Result<&Self, crate::errors::SetCrtcConfigError> {
         // Get CRTC info.
         let crtc_info: x11rb::protocol::randr::GetCrtcInfoReply = self.get_crtc_info(crtc)?;
         // Print CRTC info.
         Self::print_crtc_info(&crtc_info);
 
+        // Get current CRTC config.
+        let crtc_config_current: crate::crtc_config::CrtcConfig = self.get_crtc_config(crtc)?;
+
         // If is already
-        if crtc == crtc_existing
-            && crtc_info.x == x
-            && crtc_info.y == y
-            && crtc_info.mode == mode
-            && crtc_info.rotation == rotation
-            && crtc_info.outputs == outputs
-        {
+        if crtc == crtc_existing && crtc_config_current.equals(crtc_config) {
             // inform
             println!("CRTC config is already set!");
             // and just return.
@@ -160,38 +194,53 @@ impl Connection {
         // Set CRTC config.
         println!("Setting CRTC config to:");
         println!("\t crtc: {:?}", crtc);
-        println!("\t x: {:?}", x);
-        println!("\t y: {:?}", y);
-        println!("\t mode: {:?}", mode);
-        println!("\t rotation: {:?}", rotation);
-        println!("\t outputs: {:?}", outputs);
-        x11rb::protocol::randr::ConnectionExt::randr_set_crtc_config(
-            &self.connection,
-            crtc,
-            x11rb::CURRENT_TIME,
-            crtc_info.timestamp,
-            x,
-            y,
-            mode,
-            rotation,
-            outputs,
-        )?;
+        crtc_config.print();
+        // Transform is pending until CRTC config is set.
+        if !crtc_config_current.transform_equals(crtc_config) {
+            x11rb::protocol::randr::ConnectionExt::randr_set_crtc_transform(
+                &self.connection,
+                crtc,
+                crtc_config.transform,
+                &crtc_config.filter_name,
+                &crtc_config.filter_params,
+            )?
+            .check()?;
+        }
+        let status: x11rb::protocol::randr::SetConfig =
+            x11rb::protocol::randr::ConnectionExt::randr_set_crtc_config(
+                &self.connection,
+                crtc,
+                x11rb::CURRENT_TIME,
+                crtc_info.timestamp,
+                crtc_config.x,
+                crtc_config.y,
+                crtc_config.mode,
+                crtc_config.rotation,
+                &crtc_config.outputs,
+            )?
+            .reply()?
+            .status;
 
-        // Flush connection.
-        Ok(self.flush()?)
+        // If X server did not accept config
+        if status != x11rb::protocol::randr::SetConfig::SUCCESS {
+            // return error.
+            return Err(crate::errors::SetCrtcConfigError::SetCrtcConfigFailedError(
+                crate::errors::SetCrtcConfigFailedError::new(crtc, status),
+            ));
+        }
+
+        Ok(self)
     }
 
     // Get screen resources.
     fn screen_resources(
         &self,
     ) -> Result<x11rb::protocol::randr::GetScreenResourcesReply, x11rb::errors::ReplyError> {
-        Ok(
-            x11rb::protocol::randr::ConnectionExt::randr_get_screen_resources(
-                &self.connection,
-                self.window_root,
-            )?
-            .reply()?,
-        )
+        x11rb::protocol::randr::ConnectionExt::randr_get_screen_resources(
+            &self.connection,
+            self.window_root,
+        )?
+        .reply()
     }
 
     // Get outputs.
@@ -204,93 +253,375 @@ impl Connection {
         &self,
     ) -> Result<x11rb::protocol::randr::GetScreenResourcesCurrentReply, x11rb::errors::ReplyError>
     {
-        Ok(
-            x11rb::protocol::randr::ConnectionExt::randr_get_screen_resources_current(
-                &self.connection,
-                self.window_root,
-            )?
-            .reply()?,
-        )
+        x11rb::protocol::randr::ConnectionExt::randr_get_screen_resources_current(
+            &self.connection,
+            self.window_root,
+        )?
+        .reply()
     }
 
-    // Get free CRTC.
-    pub(crate) fn get_free_crtc(&self) -> Result<u32, crate::errors::GetFreeCrtcError> {
-        for crtc in self.screen_resources_current()?.crtcs.iter() {
-            if *crtc != 0 && self.get_crtc_info(*crtc)?.mode == 0 {
-                return Ok(*crtc);
-            }
-        }
-        Err(crate::errors::GetFreeCrtcError::NoFreeCrtcError(
-            crate::errors::NoFreeCrtcError::new(),
-        ))
+    // Get all CRTCs.
+    pub(crate) fn crtcs(&self) -> Result<Vec<u32>, x11rb::errors::ReplyError> {
+        Ok(self.screen_resources_current()?.crtcs)
     }
 
     // Set output as primary.
     pub(crate) fn set_output_primary(
         &self,
         output: u32,
-    ) -> Result<&Self, x11rb::errors::ConnectionError> {
+    ) -> Result<&Self, x11rb::errors::ReplyError> {
         x11rb::protocol::randr::ConnectionExt::randr_set_output_primary(
             &self.connection,
             self.window_root,
             output,
-        )?;
+        )?
+        .check()?;
         Ok(self)
     }
 
-    // Update screen size to fit all monitors.
-    fn update_screen_size(&self) -> Result<&Self, crate::errors::UpdateScreenSizeError> {
-        let mut total_width_px: u16 = 0;
-        let mut max_height_px: u16 = 0;
-        let mut total_width_mm: u32 = 0;
-        let mut max_height_mm: u32 = 0;
-        // Loop all of the CRTCs.
-        for crtc in self.screen_resources_current()?.crtcs {
-            // Get info.
-            let crtc_info: x11rb::protocol::randr::GetCrtcInfoReply = self.get_crtc_info(crtc)?;
-
-            // Skip ones that are not in use.
-            if crtc_info.mode == 0 {
-                continue;
-            }
+    // Get primary output.
+    pub(crate) fn get_output_primary(&self) -> Result<u32, x11rb::errors::ReplyError> {
+        Ok(
+            x11rb::protocol::randr::ConnectionExt::randr_get_output_primary(
+                &self.connection,
+                self.window_root,
+            )?
+            .reply()?
+            .output,
+        )
+    }
 
-            // Update total width and heights in pixels.
-            total_width_px += crtc_info.width;
-            max_height_px = std::cmp::max(max_height_px, crtc_info.height);
-
-            // Make sure that has only one output.
-            let outputs_len: usize = crtc_info.outputs.len();
-            if outputs_len != 1 {
-                return Err(
-                    crate::errors::UpdateScreenSizeError::CrtcDoesNotHaveExactlyOneOutput(
-                        crate::errors::CrtcDoesNotHaveExactlyOneOutput::new(outputs_len),
-                    ),
-                );
-            }
+    // Physical size in millimeters for pixels with DPI.
+    fn pixels_to_mm(pixels: u16, dpi: f64) -> u32 {
+        (f64::from(pixels) * 25.4 / dpi).round() as u32
+    }
 
-            // Get monitor info for output.
-            let output_info: x11rb::protocol::randr::GetOutputInfoReply =
-                self.get_output_info(crtc_info.outputs[0])?;
-            total_width_mm += output_info.mm_width;
-            max_height_mm = std::cmp::max(max_height_mm, output_info.mm_height);
-        }
+    // Get range of screen sizes X server supports.
+    pub(crate) fn screen_size_range(
+        &self,
+    ) -> Result<x11rb::protocol::randr::GetScreenSizeRangeReply, x11rb::errors::ReplyError> {
+        x11rb::protocol::randr::ConnectionExt::randr_get_screen_size_range(
+            &self.connection,
+            self.window_root,
+        )?
+        .reply()
+    }
 
-        // Set screen size.
+    // Get current screen size.
+    pub(crate) fn screen_size(&self) -> Result<(u16, u16), x11rb::errors::ReplyError> {
+        let geometry: x11rb::protocol::xproto::GetGeometryReply =
+            x11rb::protocol::xproto::ConnectionExt::get_geometry(
+                &self.connection,
+                self.window_root,
+            )?
+            .reply()?;
+        Ok((geometry.width, geometry.height))
+    }
+
+    // Get current physical screen size, with DPI from when connection was made.
+    pub(crate) fn screen_size_mm(&self) -> Result<(u32, u32), x11rb::errors::ReplyError> {
+        let (width_px, height_px): (u16, u16) = self.screen_size()?;
+        let screen: &x11rb::protocol::xproto::Screen =
+            &x11rb::connection::Connection::setup(&self.connection).roots[self.screen_num];
+        Ok((
+            u32::from(screen.width_in_millimeters) * u32::from(width_px)
+                / u32::from(std::cmp::max(screen.width_in_pixels, 1)),
+            u32::from(screen.height_in_millimeters) * u32::from(height_px)
+                / u32::from(std::cmp::max(screen.height_in_pixels, 1)),
+        ))
+    }
+
+    // Set screen size with physical size from DPI.
+    pub(crate) fn set_screen_size(
+        &self,
+        width_px: u16,
+        height_px: u16,
+        dpi: Option<f64>,
+    ) -> Result<&Self, x11rb::errors::ReplyError> {
+        // Physical size from DPI, so that it is same for the whole screen.
+        let dpi: f64 = dpi.unwrap_or(DEFAULT_DPI);
+        self.set_screen_size_mm(
+            width_px,
+            height_px,
+            Self::pixels_to_mm(width_px, dpi),
+            Self::pixels_to_mm(height_px, dpi),
+        )
+    }
+
+    // Set screen size with given physical size.
+    pub(crate) fn set_screen_size_mm(
+        &self,
+        width_px: u16,
+        height_px: u16,
+        width_mm: u32,
+        height_mm: u32,
+    ) -> Result<&Self, x11rb::errors::ReplyError> {
         x11rb::protocol::randr::ConnectionExt::randr_set_screen_size(
             &self.connection,
             self.window_root,
-            total_width_px,
-            max_height_px,
-            total_width_mm,
-            max_height_mm,
-        )?;
+            width_px,
+            height_px,
+            width_mm,
+            height_mm,
+        )?
+        .check()?;
         println!(
             "Set screen size to {}x{}px, {}x{}mm",
-            total_width_px, max_height_px, total_width_mm, max_height_mm
+            width_px, height_px, width_mm, height_mm
         );
         Ok(self)
     }
 
+    // Grab server, so that other clients can not change it meanwhile.
+    pub(crate) fn grab_server(&self) -> Result<&Self, x11rb::errors::ReplyError> {
+        x11rb::protocol::xproto::ConnectionExt::grab_server(&self.connection)?.check()?;
+        Ok(self)
+    }
+
+    // Ungrab server.
+    pub(crate) fn ungrab_server(&self) -> Result<&Self, x11rb::errors::ReplyError> {
+        x11rb::protocol::xproto::ConnectionExt::ungrab_server(&self.connection)?.check()?;
+        Ok(self)
+    }
+
+    // Intern atom with given name.
+    fn atom(&self, name: &[u8]) -> Result<u32, x11rb::errors::ReplyError> {
+        Ok(
+            x11rb::protocol::xproto::ConnectionExt::intern_atom(&self.connection, false, name)?
+                .reply()?
+                .atom,
+        )
+    }
+
+    // Try to take lock with given mode, that is held until connection is closed.
+    // Returns mode of the holder, if lock is already held.
+    pub(crate) fn try_lock(
+        &self,
+        mode: &str,
+    ) -> Result<Option<String>, x11rb::errors::ReplyOrIdError> {
+        // Grab server, so that no one else takes lock at the same time.
+        self.grab_server()?;
+        let result: Result<Option<String>, x11rb::errors::ReplyOrIdError> =
+            self.try_lock_grabbed(mode);
+        // Ungrab server even if taking lock failed.
+        self.ungrab_server()?;
+        result
+    }
+
+    // Try to take lock while server is grabbed.
+    fn try_lock_grabbed(
+        &self,
+        mode: &str,
+    ) -> Result<Option<String>, x11rb::errors::ReplyOrIdError> {
+        // Lock is owning a selection.
+        let selection: u32 = self.atom(b"_X11_EDID_AUTO_LOCK")?;
+        let mode_property: u32 = self.atom(b"_X11_EDID_AUTO_MODE")?;
+        let owner: u32 = x11rb::protocol::xproto::ConnectionExt::get_selection_owner(
+            &self.connection,
+            selection,
+        )?
+        .reply()?
+        .owner;
+
+        // If lock is held, return holder's mode.
+        if owner != x11rb::NONE {
+            return Ok(Some(
+                String::from_utf8_lossy(
+                    &x11rb::protocol::xproto::ConnectionExt::get_property(
+                        &self.connection,
+                        false,
+                        owner,
+                        mode_property,
+                        x11rb::protocol::xproto::AtomEnum::STRING,
+                        0,
+                        u32::MAX,
+                    )?
+                    .reply()?
+                    .value,
+                )
+                .into_owned(),
+            ));
+        }
+
+        // Otherwise create window with mode for owning the selection.
+        let window: u32 = x11rb::connection::Connection::generate_id(&self.connection)?;
+        x11rb::protocol::xproto::ConnectionExt::create_window(
+            &self.connection,
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            self.window_root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            x11rb::protocol::xproto::WindowClass::INPUT_ONLY,
+            x11rb::COPY_FROM_PARENT,
+            &x11rb::protocol::xproto::CreateWindowAux::new(),
+        )?
+        .check()?;
+        x11rb::wrapper::ConnectionExt::change_property8(
+            &self.connection,
+            x11rb::protocol::xproto::PropMode::REPLACE,
+            window,
+            mode_property,
+            x11rb::protocol::xproto::AtomEnum::STRING,
+            mode.as_bytes(),
+        )?
+        .check()?;
+        x11rb::protocol::xproto::ConnectionExt::set_selection_owner(
+            &self.connection,
+            window,
+            selection,
+            x11rb::CURRENT_TIME,
+        )?
+        .check()?;
+        Ok(None)
+    }
+
+    // Get X resources from root window's RESOURCE_MANAGER property.
+    fn resource_manager(&self) -> Result<String, x11rb::errors::ReplyError> {
+        Ok(String::from_utf8_lossy(
+            &x11rb::protocol::xproto::ConnectionExt::get_property(
+                &self.connection,
+                false,
+                self.window_root,
+                x11rb::protocol::xproto::AtomEnum::RESOURCE_MANAGER,
+                x11rb::protocol::xproto::AtomEnum::STRING,
+                0,
+                u32::MAX,
+            )?
+            .reply()?
+            .value,
+        )
+        .into_owned())
+    }
+
+    // Set X resources, keeping other existing resources.
+    pub(crate) fn set_resources(
+        &self,
+        resources: &[(&str, String)],
+    ) -> Result<&Self, x11rb::errors::ReplyError> {
+        // Keep existing resources that are not being set.
+        let mut lines: Vec<String> = self
+            .resource_manager()?
+            .lines()
+            .filter(|line| {
+                !resources.iter().any(|(name, _)| {
+                    line.split_once(':')
+                        .is_some_and(|(line_name, _)| line_name.trim() == *name)
+                })
+            })
+            .map(|line| line.to_string())
+            .collect();
+
+        // Add resources.
+        for (name, value) in resources {
+            println!("Setting resource {} to {}", name, value);
+            lines.push(format!("{}:\t{}", name, value));
+        }
+
+        // Write them back.
+        x11rb::wrapper::ConnectionExt::change_property8(
+            &self.connection,
+            x11rb::protocol::xproto::PropMode::REPLACE,
+            self.window_root,
+            x11rb::protocol::xproto::AtomEnum::RESOURCE_MANAGER,
+            x11rb::protocol::xproto::AtomEnum::STRING,
+            (lines.join("\n") + "\n").as_bytes(),
+        )?;
+        self.flush()?;
+        Ok(self)
+    }
+
+    // Regenerate cached information that changes when monitors change.
+    pub(crate) fn refresh(&mut self) -> Result<&Self, x11rb::errors::ReplyError> {
+        // Generate a map of mode info id to mode info.
+        self.mode_info_map.clear();
+        for mode_info in self.screen_resources()?.modes {
+            self.mode_info_map.insert(mode_info.id, mode_info);
+        }
+        Ok(self)
+    }
+
+    // Listen for RandR screen, output and output property change events.
+    pub(crate) fn select_randr_input(&self) -> Result<&Self, x11rb::errors::ReplyError> {
+        // Events for outputs need at least RandR 1.2.
+        x11rb::protocol::randr::ConnectionExt::randr_query_version(&self.connection, 1, 5)?
+            .reply()?;
+        x11rb::protocol::randr::ConnectionExt::randr_select_input(
+            &self.connection,
+            self.window_root,
+            x11rb::protocol::randr::NotifyMask::SCREEN_CHANGE
+                | x11rb::protocol::randr::NotifyMask::OUTPUT_CHANGE
+                | x11rb::protocol::randr::NotifyMask::OUTPUT_PROPERTY,
+        )?
+        .check()?;
+        Ok(self)
+    }
+
+    // Is event a RandR change event?
+    fn is_randr_event(event: &x11rb::protocol::Event) -> bool {
+        matches!(
+            event,
+            x11rb::protocol::Event::RandrScreenChangeNotify(_)
+                | x11rb::protocol::Event::RandrNotify(_)
+        )
+    }
+
+    // Wait for next RandR change event.
+    pub(crate) fn wait_for_randr_event(&self) -> Result<&Self, x11rb::errors::ConnectionError> {
+        loop {
+            let event: x11rb::protocol::Event =
+                x11rb::connection::Connection::wait_for_event(&self.connection)?;
+            if Self::is_randr_event(&event) {
+                return Ok(self);
+            }
+        }
+    }
+
+    // Wait for next RandR change event until timeout, returns whether there was one.
+    pub(crate) fn wait_for_randr_event_timeout(
+        &self,
+        timeout: std::time::Duration,
+    ) -> Result<bool, x11rb::errors::ConnectionError> {
+        let interval: std::time::Duration =
+            std::cmp::min(timeout, std::time::Duration::from_millis(100));
+        let start: std::time::Instant = std::time::Instant::now();
+        loop {
+            while let Some(event) = x11rb::connection::Connection::poll_for_event(&self.connection)?
+            {
+                if Self::is_randr_event(&event) {
+                    return Ok(true);
+                }
+            }
+            if start.elapsed() >= timeout {
+                return Ok(false);
+            }
+            std::thread::sleep(interval);
+        }
+    }
+
+    // Wait until there has not been RandR change events for settle duration.
+    pub(crate) fn wait_for_settle(
+        &self,
+        settle: std::time::Duration,
+    ) -> Result<&Self, x11rb::errors::ConnectionError> {
+        let interval: std::time::Duration =
+            std::cmp::min(settle, std::time::Duration::from_millis(100));
+        let mut last_event: std::time::Instant = std::time::Instant::now();
+        while last_event.elapsed() < settle {
+            std::thread::sleep(interval);
+            while let Some(event) = x11rb::connection::Connection::poll_for_event(&self.connection)?
+            {
+                if Self::is_randr_event(&event) {
+                    last_event = std::time::Instant::now();
+                }
+            }
+        }
+        Ok(self)
+    }
+
     // Flush connection.
     fn flush(&self) -> Result<&Self, x11rb::errors::ConnectionError> {
         x11rb::connection::Connection::flush(&self.connection)?;
@@ -298,8 +629,7 @@ impl Connection {
     }
 
     // End connection.
-    pub(crate) fn end(&self) -> Result<&Self, crate::errors::ConnectionEndError> {
-        self.update_screen_size()?;
-        Ok(self.flush()?)
+    pub(crate) fn end(&self) -> Result<&Self, x11rb::errors::ConnectionError> {
+        self.flush()
     }
 }
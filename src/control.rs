@@ -0,0 +1,403 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// How long to wait for response, applying can take long with hooks.
+const RESPONSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+// Requests to watch mode.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub(crate) enum Request {
+    // Status of watch mode.
+    Status,
+    // List matching profiles.
+    List,
+    // List monitors that are connected now.
+    Monitors,
+    // Apply given profile or selected monitor group.
+    Apply { profile: Option<String> },
+    // Apply next matching monitor group.
+    Cycle,
+    // Reload config.
+    Reload,
+    // Pause automatic applying.
+    Pause,
+    // Resume automatic applying.
+    Resume,
+}
+
+// Profile in response.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Profile {
+    // Key for profile, name or index of monitor group.
+    pub(crate) key: String,
+    // Description of profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<String>,
+}
+
+// Response from watch mode.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Response {
+    // Did request succeed?
+    pub(crate) ok: bool,
+    // Error, if request failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+    // Applied profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) profile: Option<String>,
+    // Is automatic applying paused?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) paused: Option<bool>,
+    // Connected monitors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) monitors: Option<Vec<String>>,
+    // Matching profiles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) profiles: Option<Vec<Profile>>,
+    // Config file of watch mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) config: Option<String>,
+}
+
+// Methods for response.
+impl Response {
+    // Successful response.
+    pub(crate) fn ok() -> Self {
+        Response {
+            ok: true,
+            ..Default::default()
+        }
+    }
+
+    // Failed response with error.
+    pub(crate) fn error(error: impl std::fmt::Display) -> Self {
+        Response {
+            ok: false,
+            error: Some(error.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+// Request for running watch mode, if command can be handled by it.
+pub(crate) fn request(arguments: &crate::arguments::Arguments) -> Option<Request> {
+    // Dry run is always done by this instance.
+    if arguments.dry_run {
+        return None;
+    }
+    match arguments.command {
+        crate::arguments::Command::Apply if arguments.list_profiles => Some(Request::List),
+        crate::arguments::Command::Apply => Some(Request::Apply {
+            profile: arguments.profile.clone(),
+        }),
+        crate::arguments::Command::Cycle => Some(Request::Cycle),
+        crate::arguments::Command::Watch | crate::arguments::Command::InstallService => None,
+        crate::arguments::Command::Status => Some(Request::Status),
+        crate::arguments::Command::Reload => Some(Request::Reload),
+        crate::arguments::Command::Pause => Some(Request::Pause),
+        crate::arguments::Command::Resume => Some(Request::Resume),
+    }
+}
+
+// Let running watch mode handle command, if there is one.
+// Returns whether it was handled.
+pub(crate) fn delegate(
+    arguments: &crate::arguments::Arguments,
+) -> Result<bool, crate::errors::ControlError> {
+    let request: Request = match request(arguments) {
+        Some(request) => request,
+        None => return Ok(false),
+    };
+    let path: std::path::PathBuf = match socket_path() {
+        Ok(path) => path,
+        Err(error) if arguments.command.is_control() => return Err(error.into()),
+        Err(_) => return Ok(false),
+    };
+    // Applying is only left to watch mode, when it would do the same.
+    if !arguments.command.is_control() {
+        let status: Response = match send(&path, &Request::Status)? {
+            Some(status) => status,
+            None => return Ok(false),
+        };
+        can_delegate(arguments, &status)?;
+    }
+    let response: Response = match send(&path, &request)? {
+        Some(response) => response,
+        // Controlling commands need watch mode.
+        None if arguments.command.is_control() => {
+            return Err(crate::errors::ControlError::NoWatchRunningError(
+                crate::errors::NoWatchRunningError::new(),
+            ))
+        }
+        None => return Ok(false),
+    };
+    if !response.ok {
+        return Err(crate::errors::ControlError::ControlFailedError(
+            crate::errors::ControlFailedError::new(response.error.unwrap_or_default()),
+        ));
+    }
+    print(&request, &response);
+    Ok(true)
+}
+
+// Can applying be left to watch mode with given status?
+fn can_delegate(
+    arguments: &crate::arguments::Arguments,
+    status: &Response,
+) -> Result<(), crate::errors::DelegateRefusedError> {
+    if arguments.wait.is_some() {
+        return Err(crate::errors::DelegateRefusedError::new(
+            "it does not support --wait".to_string(),
+        ));
+    }
+    let config: String = config_path(&arguments.config_file_path);
+    if status.config.as_deref() != Some(config.as_str()) {
+        return Err(crate::errors::DelegateRefusedError::new(format!(
+            "it uses config file {} instead of {}",
+            status.config.as_deref().unwrap_or_default(),
+            config
+        )));
+    }
+    Ok(())
+}
+
+// Print response from watch mode like the command would print its result.
+fn print(request: &Request, response: &Response) {
+    match request {
+        Request::Status => {
+            match response.paused {
+                Some(true) => println!("Watch mode is running, paused!"),
+                _ => println!("Watch mode is running!"),
+            }
+            if let Some(config) = &response.config {
+                println!("Config file: {}", config);
+            }
+            match &response.profile {
+                Some(key) => println!("Applied {}!", crate::config::label(key)),
+                None => println!("No monitor group applied!"),
+            }
+            if let Some(monitors) = &response.monitors {
+                println!("Connected monitors:");
+                for monitor in monitors {
+                    println!("\t {}", monitor);
+                }
+            }
+        }
+        Request::List => {
+            crate::selection::print_profiles(response.profiles.as_deref().unwrap_or_default())
+        }
+        Request::Monitors => {
+            for monitor in response.monitors.as_deref().unwrap_or_default() {
+                println!("{}", monitor);
+            }
+        }
+        Request::Apply { .. } => {
+            if let Some(key) = &response.profile {
+                println!("Using {}!", crate::config::label(key));
+            }
+            println!("Done!");
+        }
+        Request::Cycle => {
+            if let Some(key) = &response.profile {
+                println!("Cycling to {}!", crate::config::label(key));
+            }
+            println!("Done!");
+        }
+        Request::Reload => println!("Reloaded config!"),
+        Request::Pause => println!("Paused automatic applying!"),
+        Request::Resume => println!("Resumed automatic applying!"),
+    }
+}
+
+// Config file path as absolute path, so that instances can compare them.
+pub(crate) fn config_path(config_file_path: &str) -> String {
+    std::fs::canonicalize(config_file_path)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| config_file_path.to_string())
+}
+
+// Path to control socket for current display.
+// Socket is only in runtime directory, since other users cannot access it.
+fn socket_path() -> Result<std::path::PathBuf, crate::errors::NoRuntimeDirError> {
+    let directory: std::path::PathBuf = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) if !runtime_dir.is_empty() => std::path::PathBuf::from(runtime_dir),
+        _ => return Err(crate::errors::NoRuntimeDirError::new()),
+    };
+    let display: String = std::env::var("DISPLAY")
+        .unwrap_or_default()
+        .replace('/', "_");
+    Ok(directory.join(format!("x11_edid_auto{}.sock", display)))
+}
+
+// Listen for requests and send them to watch mode with channel for response.
+pub(crate) fn listen<E: Send + 'static>(
+    sender: std::sync::mpsc::Sender<E>,
+    event: fn(Request, std::sync::mpsc::Sender<Response>) -> E,
+) -> Result<(), crate::errors::ControlError> {
+    // Remove socket left from earlier run, lock makes sure it is not in use.
+    let path: std::path::PathBuf = socket_path()?;
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    let listener: std::os::unix::net::UnixListener = std::os::unix::net::UnixListener::bind(&path)?;
+    println!("Listening for requests in {:?}", path);
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sender: std::sync::mpsc::Sender<E> = sender.clone();
+            std::thread::spawn(move || serve(stream, sender, event));
+        }
+    });
+    Ok(())
+}
+
+// Serve requests from one client, one JSON object per line.
+fn serve<E>(
+    stream: std::os::unix::net::UnixStream,
+    sender: std::sync::mpsc::Sender<E>,
+    event: fn(Request, std::sync::mpsc::Sender<Response>) -> E,
+) {
+    let mut writer: &std::os::unix::net::UnixStream = &stream;
+    for line in std::io::BufRead::lines(std::io::BufReader::new(&stream)) {
+        let line: String = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response: Response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => forward(&sender, event, request),
+            Err(error) => Response::error(error),
+        };
+        let json: String = match serde_json::to_string(&response) {
+            Ok(json) => json,
+            Err(_) => return,
+        };
+        if std::io::Write::write_all(&mut writer, format!("{}\n", json).as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+// Send request to watch mode and wait for its response.
+pub(crate) fn forward<E>(
+    sender: &std::sync::mpsc::Sender<E>,
+    event: fn(Request, std::sync::mpsc::Sender<Response>) -> E,
+    request: Request,
+) -> Response {
+    let (response_sender, response_receiver): (
+        std::sync::mpsc::Sender<Response>,
+        std::sync::mpsc::Receiver<Response>,
+    ) = std::sync::mpsc::channel();
+    if sender.send(event(request, response_sender)).is_err() {
+        return Response::error("Watch mode stopped!");
+    }
+    response_receiver
+        .recv()
+        .unwrap_or_else(|_| Response::error("Watch mode stopped!"))
+}
+
+// Send request to running watch mode.
+// Returns None when watch mode is not running.
+fn send(
+    path: &std::path::Path,
+    request: &Request,
+) -> Result<Option<Response>, crate::errors::ControlError> {
+    let stream: std::os::unix::net::UnixStream = match std::os::unix::net::UnixStream::connect(path)
+    {
+        Ok(stream) => stream,
+        Err(_) => return Ok(None),
+    };
+    stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+    stream.set_write_timeout(Some(RESPONSE_TIMEOUT))?;
+    let mut writer: &std::os::unix::net::UnixStream = &stream;
+    std::io::Write::write_all(
+        &mut writer,
+        format!("{}\n", serde_json::to_string(request)?).as_bytes(),
+    )?;
+    let mut line: String = String::new();
+    match std::io::BufRead::read_line(&mut std::io::BufReader::new(&stream), &mut line) {
+        Ok(_) => {}
+        Err(error)
+            if matches!(
+                error.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ) =>
+        {
+            return Err(crate::errors::ControlTimedOutError::new(RESPONSE_TIMEOUT).into())
+        }
+        Err(error) => return Err(error.into()),
+    }
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+#[cfg(test)]
+mod tests {
+    // Arguments for applying with config file and wait.
+    fn arguments(config_file_path: &str, wait: Option<f64>) -> crate::arguments::Arguments {
+        crate::arguments::Arguments {
+            command: crate::arguments::Command::Apply,
+            config_file_path: config_file_path.to_string(),
+            dry_run: false,
+            profile: None,
+            list_profiles: false,
+            wait,
+            systemd: false,
+        }
+    }
+
+    // Status of watch mode using config file.
+    fn status(config_file_path: &str) -> super::Response {
+        super::Response {
+            config: Some(super::config_path(config_file_path)),
+            ..super::Response::ok()
+        }
+    }
+
+    #[test]
+    fn applying_is_delegated_with_same_config() {
+        assert!(super::can_delegate(&arguments("Cargo.toml", None), &status("Cargo.toml")).is_ok());
+        // Paths are compared as absolute paths.
+        assert!(super::can_delegate(
+            &arguments("./src/../Cargo.toml", None),
+            &status("Cargo.toml")
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn applying_with_other_config_is_not_delegated() {
+        assert!(super::can_delegate(&arguments("README.md", None), &status("Cargo.toml")).is_err());
+        assert!(
+            super::can_delegate(&arguments("Cargo.toml", None), &super::Response::ok()).is_err()
+        );
+    }
+
+    #[test]
+    fn applying_with_wait_is_not_delegated() {
+        let error: crate::errors::DelegateRefusedError =
+            super::can_delegate(&arguments("Cargo.toml", Some(5.0)), &status("Cargo.toml"))
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Watch mode is running and applies monitors, but it does not support --wait!"
+        );
+    }
+
+    #[test]
+    fn status_request_is_tagged_with_command() {
+        assert_eq!(
+            serde_json::to_string(&super::Request::Status).unwrap(),
+            r#"{"command":"status"}"#
+        );
+        let request: super::Request =
+            serde_json::from_str(r#"{"command":"apply","profile":"desk"}"#).unwrap();
+        assert!(matches!(
+            request,
+            super::Request::Apply { profile: Some(profile) } if profile == "desk"
+        ));
+    }
+}
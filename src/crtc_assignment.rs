@@ -0,0 +1,81 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Assign CRTCs for outputs, so that every output gets a different CRTC from its candidates.
+// Candidates for each output are given in order of preference.
+// Returns assigned CRTC for each output or None if there is no such assignment.
+pub(crate) fn assign(candidates: &[Vec<u32>]) -> Option<Vec<u32>> {
+    // Map of CRTC to index of output it is assigned to.
+    let mut assigned: std::collections::HashMap<u32, usize> = std::collections::HashMap::new();
+
+    // Find augmenting path for each output.
+    for output_index in 0..candidates.len() {
+        if !augment(
+            candidates,
+            output_index,
+            &mut assigned,
+            &mut std::collections::HashSet::new(),
+        ) {
+            return None;
+        }
+    }
+
+    // Collect CRTCs in order of outputs.
+    let mut crtcs: Vec<u32> = vec![0; candidates.len()];
+    for (crtc, output_index) in assigned {
+        crtcs[output_index] = crtc;
+    }
+    Some(crtcs)
+}
+
+// Try to assign CRTC for output, moving other outputs to their other candidates if needed.
+fn augment(
+    candidates: &[Vec<u32>],
+    output_index: usize,
+    assigned: &mut std::collections::HashMap<u32, usize>,
+    visited: &mut std::collections::HashSet<u32>,
+) -> bool {
+    for crtc in &candidates[output_index] {
+        // Skip CRTCs that were already tried on this path.
+        if !visited.insert(*crtc) {
+            continue;
+        }
+        // If CRTC is free or output using it can be moved
+        let is_available: bool = match assigned.get(crtc).copied() {
+            None => true,
+            Some(other_output_index) => augment(candidates, other_output_index, assigned, visited),
+        };
+        // assign it.
+        if is_available {
+            assigned.insert(*crtc, output_index);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn assigns_preferred_candidates() {
+        assert_eq!(super::assign(&[vec![1, 2], vec![2, 3]]), Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn moves_output_to_other_candidate() {
+        // First output takes CRTC 1, but has to move to CRTC 2 for second output.
+        assert_eq!(super::assign(&[vec![1, 2], vec![1]]), Some(vec![2, 1]));
+    }
+
+    #[test]
+    fn fails_when_unsatisfiable() {
+        assert_eq!(super::assign(&[vec![1], vec![1]]), None);
+        assert_eq!(super::assign(&[vec![1, 2], vec![1, 2], vec![2]]), None);
+        assert_eq!(super::assign(&[vec![]]), None);
+    }
+
+    #[test]
+    fn assigns_nothing_for_no_outputs() {
+        assert_eq!(super::assign(&[]), Some(vec![]));
+    }
+}
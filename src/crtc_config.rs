@@ -0,0 +1,139 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Structure for CRTC config.
+#[derive(Clone)]
+pub(crate) struct CrtcConfig {
+    // Position.
+    pub(crate) x: i16,
+    pub(crate) y: i16,
+    // Mode id, 0 when disabled.
+    pub(crate) mode: u32,
+    // Rotation.
+    pub(crate) rotation: x11rb::protocol::randr::Rotation,
+    // Transform.
+    pub(crate) transform: x11rb::protocol::render::Transform,
+    // Filter for transform.
+    pub(crate) filter_name: Vec<u8>,
+    pub(crate) filter_params: Vec<x11rb::protocol::render::Fixed>,
+    // Outputs.
+    pub(crate) outputs: Vec<u32>,
+}
+
+// Methods for CRTC config.
+impl CrtcConfig {
+    // Create a new CRTC config with scale.
+    pub(crate) fn new(
+        x: i16,
+        y: i16,
+        mode: u32,
+        rotation: x11rb::protocol::randr::Rotation,
+        scale_x: f64,
+        scale_y: f64,
+        outputs: Vec<u32>,
+    ) -> Self {
+        CrtcConfig {
+            x,
+            y,
+            mode,
+            rotation,
+            transform: Self::scale_transform(scale_x, scale_y),
+            filter_name: if scale_x == 1.0 && scale_y == 1.0 {
+                b"nearest".to_vec()
+            } else {
+                b"bilinear".to_vec()
+            },
+            filter_params: Vec::new(),
+            outputs,
+        }
+    }
+
+    // Create a new CRTC config for disabled CRTC.
+    pub(crate) fn disabled() -> Self {
+        Self::new(
+            0,
+            0,
+            0,
+            x11rb::protocol::randr::Rotation::ROTATE0,
+            1.0,
+            1.0,
+            Vec::new(),
+        )
+    }
+
+    // Scale as 16.16 fixed point number, as it is sent to X server.
+    fn scale_fixed(scale: f64) -> x11rb::protocol::render::Fixed {
+        (scale * 65536.0).round() as i32
+    }
+
+    // Size after scaling, rounded up like X server does for transformed CRTC.
+    pub(crate) fn scaled_size(size: u16, scale: f64) -> u16 {
+        let scaled: i64 = (i64::from(size) * i64::from(Self::scale_fixed(scale)) + 65535) / 65536;
+        scaled.clamp(0, i64::from(u16::MAX)) as u16
+    }
+
+    // Transform matrix for scale.
+    fn scale_transform(scale_x: f64, scale_y: f64) -> x11rb::protocol::render::Transform {
+        x11rb::protocol::render::Transform {
+            matrix11: Self::scale_fixed(scale_x),
+            matrix12: 0,
+            matrix13: 0,
+            matrix21: 0,
+            matrix22: Self::scale_fixed(scale_y),
+            matrix23: 0,
+            matrix31: 0,
+            matrix32: 0,
+            matrix33: 65536,
+        }
+    }
+
+    // Transform matrix as array.
+    fn transform_matrix(&self) -> [x11rb::protocol::render::Fixed; 9] {
+        [
+            self.transform.matrix11,
+            self.transform.matrix12,
+            self.transform.matrix13,
+            self.transform.matrix21,
+            self.transform.matrix22,
+            self.transform.matrix23,
+            self.transform.matrix31,
+            self.transform.matrix32,
+            self.transform.matrix33,
+        ]
+    }
+
+    // Has same transform as other config?
+    pub(crate) fn transform_equals(&self, other: &CrtcConfig) -> bool {
+        self.transform_matrix() == other.transform_matrix()
+    }
+
+    // Is disabled?
+    pub(crate) fn is_disabled(&self) -> bool {
+        self.mode == 0
+    }
+
+    // Is same as other config?
+    pub(crate) fn equals(&self, other: &CrtcConfig) -> bool {
+        self.x == other.x
+            && self.y == other.y
+            && self.mode == other.mode
+            && self.rotation == other.rotation
+            && self.outputs == other.outputs
+            && self.transform_equals(other)
+    }
+
+    // Print CRTC config.
+    pub(crate) fn print(&self) -> &Self {
+        println!("\t x: {:?}", self.x);
+        println!("\t y: {:?}", self.y);
+        println!("\t mode: {:?}", self.mode);
+        println!("\t rotation: {:?}", self.rotation);
+        println!(
+            "\t scale: {:?}x{:?}",
+            f64::from(self.transform.matrix11) / 65536.0,
+            f64::from(self.transform.matrix22) / 65536.0
+        );
+        println!("\t outputs: {:?}", self.outputs);
+        self
+    }
+}
@@ -0,0 +1,261 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Well-known name of service on session bus.
+const NAME: &str = "io.github.x11_edid_auto";
+
+// Path of service object.
+const PATH: &str = "/io/github/x11_edid_auto";
+
+// Name of service interface.
+const INTERFACE: &str = "io.github.x11_edid_auto";
+
+// D-Bus service, that forwards method calls to watch mode.
+struct Service {
+    // Send request to watch mode and wait for its response.
+    forward:
+        std::sync::Arc<dyn Fn(crate::control::Request) -> crate::control::Response + Send + Sync>,
+}
+
+// Methods for service.
+impl Service {
+    // Forward request, failing method call if request failed.
+    // Waiting for watch mode is done on another thread, so that connection keeps serving.
+    async fn request(
+        &self,
+        request: crate::control::Request,
+    ) -> Result<crate::control::Response, zbus::fdo::Error> {
+        let forward: std::sync::Arc<
+            dyn Fn(crate::control::Request) -> crate::control::Response + Send + Sync,
+        > = self.forward.clone();
+        let response: crate::control::Response = blocking::unblock(move || forward(request)).await;
+        if !response.ok {
+            return Err(zbus::fdo::Error::Failed(response.error.unwrap_or_default()));
+        }
+        Ok(response)
+    }
+}
+
+// Methods of service interface.
+#[zbus::interface(name = "io.github.x11_edid_auto")]
+impl Service {
+    // Monitors that are connected now.
+    async fn list_monitors(&self) -> Result<Vec<String>, zbus::fdo::Error> {
+        Ok(self
+            .request(crate::control::Request::Monitors)
+            .await?
+            .monitors
+            .unwrap_or_default())
+    }
+
+    // Matching profiles with their keys and descriptions.
+    async fn list_profiles(&self) -> Result<Vec<(String, String)>, zbus::fdo::Error> {
+        Ok(self
+            .request(crate::control::Request::List)
+            .await?
+            .profiles
+            .unwrap_or_default()
+            .into_iter()
+            .map(|profile| (profile.key, profile.description.unwrap_or_default()))
+            .collect())
+    }
+
+    // Apply profile with given name, or selected monitor group with empty name.
+    // Returns key of applied monitor group.
+    async fn apply_profile(&self, name: &str) -> Result<String, zbus::fdo::Error> {
+        let profile: Option<String> = Some(name.to_string()).filter(|name| !name.is_empty());
+        Ok(self
+            .request(crate::control::Request::Apply { profile })
+            .await?
+            .profile
+            .unwrap_or_default())
+    }
+
+    // Apply next matching monitor group.
+    // Returns key of applied monitor group.
+    async fn cycle(&self) -> Result<String, zbus::fdo::Error> {
+        Ok(self
+            .request(crate::control::Request::Cycle)
+            .await?
+            .profile
+            .unwrap_or_default())
+    }
+}
+
+// Structure for emitting signals from service.
+pub(crate) struct Signals {
+    // Connection to session bus, that also serves the service.
+    connection: zbus::blocking::Connection,
+}
+
+// Methods for signals.
+impl Signals {
+    // Emit signal, only printing errors.
+    fn emit<B: serde::Serialize + zbus::zvariant::DynamicType>(&self, name: &str, body: &B) {
+        if let Err(error) = self
+            .connection
+            .emit_signal(None::<()>, PATH, INTERFACE, name, body)
+        {
+            crate::log::error(&error);
+        }
+    }
+
+    // Monitor group with key was applied for connected monitors.
+    pub(crate) fn profile_applied(&self, key: &str, monitors: &[String]) {
+        self.emit("ProfileApplied", &(key, monitors));
+    }
+
+    // Connected monitors changed.
+    pub(crate) fn monitors_changed(&self, monitors: &[String]) {
+        self.emit("MonitorsChanged", &(monitors,));
+    }
+}
+
+// Serve service on session bus, sending requests to watch mode with channel for response.
+pub(crate) fn serve<E: Send + 'static>(
+    sender: std::sync::mpsc::Sender<E>,
+    event: fn(crate::control::Request, std::sync::mpsc::Sender<crate::control::Response>) -> E,
+) -> Result<Signals, zbus::Error> {
+    let service: Service = Service {
+        forward: std::sync::Arc::new(move |request| {
+            crate::control::forward(&sender, event, request)
+        }),
+    };
+    let connection: zbus::blocking::Connection = zbus::blocking::connection::Builder::session()?
+        .name(NAME)?
+        .serve_at(PATH, service)?
+        .build()?;
+    println!("Serving D-Bus service {} on session bus", NAME);
+    Ok(Signals { connection })
+}
+
+#[cfg(test)]
+mod tests {
+    // Request forwarded from service with channel for response.
+    type Forwarded = (
+        crate::control::Request,
+        std::sync::mpsc::Sender<crate::control::Response>,
+    );
+
+    // Private D-Bus daemon, that is killed when test ends.
+    struct Daemon(std::process::Child);
+    impl Drop for Daemon {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    // Answer requests like watch mode would.
+    fn answer(request: crate::control::Request) -> crate::control::Response {
+        match request {
+            crate::control::Request::Monitors => crate::control::Response {
+                monitors: Some(vec!["desk".to_string(), "laptop".to_string()]),
+                ..crate::control::Response::ok()
+            },
+            crate::control::Request::Apply { profile } => crate::control::Response {
+                profile: Some(profile.unwrap_or_else(|| "#1".to_string())),
+                ..crate::control::Response::ok()
+            },
+            _ => crate::control::Response::error("No matching monitor group!"),
+        }
+    }
+
+    #[test]
+    fn serves_on_private_bus() {
+        // Private session bus, skipping test if there is no D-Bus daemon.
+        let mut daemon: Daemon = match std::process::Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address=1"])
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+        {
+            Ok(daemon) => Daemon(daemon),
+            Err(error) => {
+                eprintln!("Skipping test without dbus-daemon: {}", error);
+                return;
+            }
+        };
+        let mut address: String = String::new();
+        std::io::BufRead::read_line(
+            &mut std::io::BufReader::new(daemon.0.stdout.take().unwrap()),
+            &mut address,
+        )
+        .unwrap();
+        std::env::set_var("DBUS_SESSION_BUS_ADDRESS", address.trim());
+
+        let (sender, receiver): (
+            std::sync::mpsc::Sender<Forwarded>,
+            std::sync::mpsc::Receiver<Forwarded>,
+        ) = std::sync::mpsc::channel();
+        let signals: super::Signals =
+            super::serve(sender, |request, response| (request, response)).unwrap();
+        // Slow apply is answered only after the next request, like when watch mode runs hooks.
+        std::thread::spawn(move || {
+            let mut pending: Option<Forwarded> = None;
+            for (request, response) in receiver {
+                if let crate::control::Request::Apply {
+                    profile: Some(profile),
+                } = &request
+                {
+                    if profile == "slow" {
+                        pending = Some((request, response));
+                        continue;
+                    }
+                }
+                let _ = response.send(answer(request));
+                if let Some((request, response)) = pending.take() {
+                    let _ = response.send(answer(request));
+                }
+            }
+        });
+
+        let connection: zbus::blocking::Connection = zbus::blocking::Connection::session().unwrap();
+        let proxy: zbus::blocking::Proxy =
+            zbus::blocking::Proxy::new(&connection, super::NAME, super::PATH, super::INTERFACE)
+                .unwrap();
+        let monitors: Vec<String> = proxy.call("ListMonitors", &()).unwrap();
+        assert_eq!(monitors, vec!["desk", "laptop"]);
+        let profile: String = proxy.call("ApplyProfile", &("desk-dual",)).unwrap();
+        assert_eq!(profile, "desk-dual");
+        let profile: String = proxy.call("ApplyProfile", &("",)).unwrap();
+        assert_eq!(profile, "#1");
+        match proxy.call::<_, _, String>("Cycle", &()) {
+            Err(zbus::Error::MethodError(name, message, _)) => {
+                assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.Failed");
+                assert_eq!(message.as_deref(), Some("No matching monitor group!"));
+            }
+            result => panic!("Cycle should fail, got {:?}", result),
+        }
+
+        let mut applied: zbus::blocking::proxy::SignalIterator =
+            proxy.receive_signal("ProfileApplied").unwrap();
+        signals.profile_applied("desk-dual", &["desk".to_string()]);
+        let (key, monitors): (String, Vec<String>) =
+            applied.next().unwrap().body().deserialize().unwrap();
+        assert_eq!(key, "desk-dual");
+        assert_eq!(monitors, vec!["desk"]);
+
+        // Pending call does not stop service from handling other calls.
+        let slow: std::thread::JoinHandle<String> = std::thread::spawn(|| {
+            let connection: zbus::blocking::Connection =
+                zbus::blocking::Connection::session().unwrap();
+            zbus::blocking::Proxy::new(&connection, super::NAME, super::PATH, super::INTERFACE)
+                .unwrap()
+                .call("ApplyProfile", &("slow",))
+                .unwrap()
+        });
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let (monitors_sender, monitors_receiver): (
+            std::sync::mpsc::Sender<Vec<String>>,
+            std::sync::mpsc::Receiver<Vec<String>>,
+        ) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = monitors_sender.send(proxy.call("ListMonitors", &()).unwrap());
+        });
+        let monitors: Vec<String> = monitors_receiver
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .expect("Service did not answer while another call was pending");
+        assert_eq!(monitors, vec!["desk", "laptop"]);
+        assert_eq!(slow.join().unwrap(), "slow");
+    }
+}
@@ -1,5 +1,7 @@
 // Forbid unsafe code.
 #![forbid(unsafe_code)]
+// Error variants are named after the errors they wrap.
+#![allow(clippy::enum_variant_names)]
 
 // No root for screen number error.
 #[derive(Debug)]
@@ -56,125 +58,59 @@ impl From<NoRootForScreenNumberError> for ConnectionNewError {
     }
 }
 
-// No free Crtc error.
+// Set CRTC config failed error.
 #[derive(Debug)]
-pub(crate) struct NoFreeCrtcError {}
-impl NoFreeCrtcError {
-    pub fn new() -> NoFreeCrtcError {
-        NoFreeCrtcError {}
-    }
+pub(crate) struct SetCrtcConfigFailedError {
+    crtc: u32,
+    status: x11rb::protocol::randr::SetConfig,
 }
-impl std::error::Error for NoFreeCrtcError {}
-impl std::fmt::Display for NoFreeCrtcError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "No free Crtc found!")
+impl SetCrtcConfigFailedError {
+    pub fn new(crtc: u32, status: x11rb::protocol::randr::SetConfig) -> SetCrtcConfigFailedError {
+        SetCrtcConfigFailedError { crtc, status }
     }
 }
-
-// Collects all of the errors that can occur when getting free Crtc.
-#[derive(Debug)]
-pub(crate) enum GetFreeCrtcError {
-    ReplyError(x11rb::errors::ReplyError),
-    NoFreeCrtcError(NoFreeCrtcError),
-}
-impl std::fmt::Display for GetFreeCrtcError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            GetFreeCrtcError::ReplyError(e) => write!(f, "Reply error:\n{}", e),
-            GetFreeCrtcError::NoFreeCrtcError(e) => write!(f, "No free Crtc error:\n{}", e),
-        }
-    }
-}
-impl From<x11rb::errors::ReplyError> for GetFreeCrtcError {
-    fn from(err: x11rb::errors::ReplyError) -> Self {
-        GetFreeCrtcError::ReplyError(err)
-    }
-}
-impl From<NoFreeCrtcError> for GetFreeCrtcError {
-    fn from(err: NoFreeCrtcError) -> Self {
-        GetFreeCrtcError::NoFreeCrtcError(err)
-    }
-}
-
-// CRTC does not have exactly one output error.
-#[derive(Debug)]
-pub(crate) struct CrtcDoesNotHaveExactlyOneOutput {
-    outputs_len: usize,
-}
-impl CrtcDoesNotHaveExactlyOneOutput {
-    pub fn new(outputs_len: usize) -> CrtcDoesNotHaveExactlyOneOutput {
-        CrtcDoesNotHaveExactlyOneOutput { outputs_len }
-    }
-}
-impl std::error::Error for CrtcDoesNotHaveExactlyOneOutput {}
-impl std::fmt::Display for CrtcDoesNotHaveExactlyOneOutput {
+impl std::error::Error for SetCrtcConfigFailedError {}
+impl std::fmt::Display for SetCrtcConfigFailedError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "Expected CRTC to have only one output, it had {} outputs!",
-            self.outputs_len
+            "Setting config for CRTC {} failed with status {:?}!",
+            self.crtc, self.status
         )
     }
 }
 
-// Collects all of the errors that can occur when updating screen size.
+// Collects all of the errors that can occur when setting CRTC config.
 #[derive(Debug)]
-pub(crate) enum UpdateScreenSizeError {
+pub(crate) enum SetCrtcConfigError {
     ReplyError(x11rb::errors::ReplyError),
-    CrtcDoesNotHaveExactlyOneOutput(CrtcDoesNotHaveExactlyOneOutput),
+    SetCrtcConfigFailedError(SetCrtcConfigFailedError),
     ConnectionError(x11rb::errors::ConnectionError),
 }
-impl std::fmt::Display for UpdateScreenSizeError {
+impl std::fmt::Display for SetCrtcConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            UpdateScreenSizeError::ReplyError(e) => write!(f, "Reply error:\n{}", e),
-            UpdateScreenSizeError::CrtcDoesNotHaveExactlyOneOutput(e) => {
-                write!(f, "CRTC does not have exactly one output error:\n{}", e)
+            SetCrtcConfigError::ReplyError(e) => write!(f, "Reply error:\n{}", e),
+            SetCrtcConfigError::SetCrtcConfigFailedError(e) => {
+                write!(f, "Set CRTC config failed error:\n{}", e)
             }
-            UpdateScreenSizeError::ConnectionError(e) => write!(f, "Connection error:\n{}", e),
+            SetCrtcConfigError::ConnectionError(e) => write!(f, "Connection error:\n{}", e),
         }
     }
 }
-impl From<x11rb::errors::ReplyError> for UpdateScreenSizeError {
+impl From<x11rb::errors::ReplyError> for SetCrtcConfigError {
     fn from(err: x11rb::errors::ReplyError) -> Self {
-        UpdateScreenSizeError::ReplyError(err)
+        SetCrtcConfigError::ReplyError(err)
     }
 }
-impl From<CrtcDoesNotHaveExactlyOneOutput> for UpdateScreenSizeError {
-    fn from(err: CrtcDoesNotHaveExactlyOneOutput) -> Self {
-        UpdateScreenSizeError::CrtcDoesNotHaveExactlyOneOutput(err)
-    }
-}
-impl From<x11rb::errors::ConnectionError> for UpdateScreenSizeError {
-    fn from(err: x11rb::errors::ConnectionError) -> Self {
-        UpdateScreenSizeError::ConnectionError(err)
-    }
-}
-
-// Collects all of the errors that can occur when ending connection.
-#[derive(Debug)]
-pub(crate) enum ConnectionEndError {
-    ConnectionError(x11rb::errors::ConnectionError),
-    UpdateScreenSizeError(UpdateScreenSizeError),
-}
-impl std::fmt::Display for ConnectionEndError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            ConnectionEndError::ConnectionError(e) => write!(f, "Connect error:\n{}", e),
-            ConnectionEndError::UpdateScreenSizeError(e) => {
-                write!(f, "Update screen size error:\n{}", e)
-            }
-        }
+impl From<SetCrtcConfigFailedError> for SetCrtcConfigError {
+    fn from(err: SetCrtcConfigFailedError) -> Self {
+        SetCrtcConfigError::SetCrtcConfigFailedError(err)
     }
 }
-impl From<x11rb::errors::ConnectionError> for ConnectionEndError {
+impl From<x11rb::errors::ConnectionError> for SetCrtcConfigError {
     fn from(err: x11rb::errors::ConnectionError) -> Self {
-        ConnectionEndError::ConnectionError(err)
-    }
-}
-impl From<UpdateScreenSizeError> for ConnectionEndError {
-    fn from(err: UpdateScreenSizeError) -> Self {
-        ConnectionEndError::UpdateScreenSizeError(err)
+        SetCrtcConfigError::ConnectionError(err)
     }
 }
 
@@ -243,38 +179,6 @@ impl From<NoModesError> for MonitorModeInfoError {
     }
 }
 
-// Collects all of the errors that can occur when enabling monitor.
-#[derive(Debug)]
-pub(crate) enum MonitorEnableError {
-    ReplyError(x11rb::errors::ReplyError),
-    GetFreeCrtcError(GetFreeCrtcError),
-    MonitorModeInfoError(MonitorModeInfoError),
-}
-impl std::fmt::Display for MonitorEnableError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            MonitorEnableError::ReplyError(e) => write!(f, "Reply error:\n{}", e),
-            MonitorEnableError::GetFreeCrtcError(e) => write!(f, "Get free Crtc {:?}", e),
-            MonitorEnableError::MonitorModeInfoError(e) => write!(f, "Monitor mode info {:?}", e),
-        }
-    }
-}
-impl From<x11rb::errors::ReplyError> for MonitorEnableError {
-    fn from(err: x11rb::errors::ReplyError) -> Self {
-        MonitorEnableError::ReplyError(err)
-    }
-}
-impl From<GetFreeCrtcError> for MonitorEnableError {
-    fn from(err: GetFreeCrtcError) -> Self {
-        MonitorEnableError::GetFreeCrtcError(err)
-    }
-}
-impl From<MonitorModeInfoError> for MonitorEnableError {
-    fn from(err: MonitorModeInfoError) -> Self {
-        MonitorEnableError::MonitorModeInfoError(err)
-    }
-}
-
 // Invalid arguments error.
 #[derive(Debug)]
 pub(crate) struct InvalidArgumentsError {
@@ -288,7 +192,11 @@ impl InvalidArgumentsError {
 impl std::error::Error for InvalidArgumentsError {}
 impl std::fmt::Display for InvalidArgumentsError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "Usage: {} <config file>", self.script)
+        write!(
+            f,
+            "Usage: {} [apply|cycle|watch] [--dry-run] [--profile <name>] [--list-profiles] [--wait <secs>] [--systemd] <config file>\n       {} status|reload|pause|resume\n       {} install-service [--dry-run] <config file>",
+            self.script, self.script, self.script
+        )
     }
 }
 
@@ -345,44 +253,39 @@ impl std::fmt::Display for MonitorNotFoundError {
     }
 }
 
-// Failed to transform to i16 error.
-#[derive(Debug)]
-pub(crate) struct TryIntoI16Error {
-    name: String,
-    try_from_int_error: std::num::TryFromIntError,
-}
-impl TryIntoI16Error {
-    pub fn new(name: String, try_from_int_error: std::num::TryFromIntError) -> TryIntoI16Error {
-        TryIntoI16Error {
-            name,
-            try_from_int_error,
-        }
-    }
-}
-impl std::error::Error for TryIntoI16Error {}
-impl std::fmt::Display for TryIntoI16Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
-            f,
-            "Failed to tranform {} to i16 and gotted try from int error {:?}!",
-            self.name, self.try_from_int_error
-        )
-    }
-}
-
 // No monitor groups with all monitors present error.
 #[derive(Debug)]
 pub(crate) struct NoMonitorGroupWithAllMonitorsPresentError {
     monitor_infos_for_monitors: Vec<Vec<String>>,
+    // Connected monitors without alias, with output name and monitor ID.
+    unknown_monitors: Vec<(String, String)>,
 }
 impl NoMonitorGroupWithAllMonitorsPresentError {
     pub fn new(
         monitor_infos_for_monitors: Vec<Vec<String>>,
+        unknown_monitors: Vec<(String, String)>,
     ) -> NoMonitorGroupWithAllMonitorsPresentError {
         NoMonitorGroupWithAllMonitorsPresentError {
             monitor_infos_for_monitors,
+            unknown_monitors,
         }
     }
+
+    // Short description with unknown monitors as lines ready to be copied to config.
+    pub(crate) fn summary(&self) -> String {
+        if self.unknown_monitors.is_empty() {
+            return "All connected monitors are known, but no monitor group matches them."
+                .to_string();
+        }
+        format!(
+            "Unknown monitors, add to [monitors] in config:\n{}",
+            self.unknown_monitors
+                .iter()
+                .map(|(name, monitor_id)| format!("{} = {:?}", name, monitor_id))
+                .collect::<Vec<String>>()
+                .join("\n")
+        )
+    }
 }
 impl std::error::Error for NoMonitorGroupWithAllMonitorsPresentError {}
 impl std::fmt::Display for NoMonitorGroupWithAllMonitorsPresentError {
@@ -406,23 +309,677 @@ impl std::fmt::Display for NoMonitorGroupWithAllMonitorsPresentError {
     }
 }
 
+// Screen size too large error.
+#[derive(Debug)]
+pub(crate) struct ScreenSizeTooLargeError {
+    width: u32,
+    height: u32,
+    max_width: u16,
+    max_height: u16,
+}
+impl ScreenSizeTooLargeError {
+    pub fn new(
+        width: u32,
+        height: u32,
+        max_width: u16,
+        max_height: u16,
+    ) -> ScreenSizeTooLargeError {
+        ScreenSizeTooLargeError {
+            width,
+            height,
+            max_width,
+            max_height,
+        }
+    }
+}
+impl std::error::Error for ScreenSizeTooLargeError {}
+impl std::fmt::Display for ScreenSizeTooLargeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Screen size {}x{} exceeds maximum framebuffer size {}x{}!",
+            self.width, self.height, self.max_width, self.max_height
+        )
+    }
+}
+
+// Mirror monitor not in group error.
+#[derive(Debug)]
+pub(crate) struct MirrorMonitorNotInGroupError {
+    edid: String,
+}
+impl MirrorMonitorNotInGroupError {
+    pub fn new(edid: String) -> MirrorMonitorNotInGroupError {
+        MirrorMonitorNotInGroupError { edid }
+    }
+}
+impl std::error::Error for MirrorMonitorNotInGroupError {}
+impl std::fmt::Display for MirrorMonitorNotInGroupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Mirrored monitor {} is not in monitor group's monitors!",
+            self.edid
+        )
+    }
+}
+
+// No CRTC assignment error.
+#[derive(Debug)]
+pub(crate) struct NoCrtcAssignmentError {
+    monitor_ids: Vec<String>,
+}
+impl NoCrtcAssignmentError {
+    pub fn new(monitor_ids: Vec<String>) -> NoCrtcAssignmentError {
+        NoCrtcAssignmentError { monitor_ids }
+    }
+}
+impl std::error::Error for NoCrtcAssignmentError {}
+impl std::fmt::Display for NoCrtcAssignmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Could not find CRTCs that can drive all of the monitors {:?} at the same time!",
+            self.monitor_ids
+        )
+    }
+}
+
+// Profile not found error.
+#[derive(Debug)]
+pub(crate) struct ProfileNotFoundError {
+    name: String,
+}
+impl ProfileNotFoundError {
+    pub fn new(name: String) -> ProfileNotFoundError {
+        ProfileNotFoundError { name }
+    }
+}
+impl std::error::Error for ProfileNotFoundError {}
+impl std::fmt::Display for ProfileNotFoundError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Profile {:?} not found in config file!", self.name)
+    }
+}
+
+// Duplicate profile name error.
+#[derive(Debug)]
+pub(crate) struct DuplicateProfileNameError {
+    name: String,
+}
+impl DuplicateProfileNameError {
+    pub fn new(name: String) -> DuplicateProfileNameError {
+        DuplicateProfileNameError { name }
+    }
+}
+impl std::error::Error for DuplicateProfileNameError {}
+impl std::fmt::Display for DuplicateProfileNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Profile name {:?} is used more than once!", self.name)
+    }
+}
+
+// Reserved profile name error.
+#[derive(Debug)]
+pub(crate) struct ReservedProfileNameError {
+    name: String,
+}
+impl ReservedProfileNameError {
+    pub fn new(name: String) -> ReservedProfileNameError {
+        ReservedProfileNameError { name }
+    }
+}
+impl std::error::Error for ReservedProfileNameError {}
+impl std::fmt::Display for ReservedProfileNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Profile name {:?} starts with '#', which is reserved for monitor groups without name!",
+            self.name
+        )
+    }
+}
+
+// Profile monitors not present error.
+#[derive(Debug)]
+pub(crate) struct ProfileMonitorsNotPresentError {
+    name: String,
+    monitor_ids: Vec<String>,
+}
+impl ProfileMonitorsNotPresentError {
+    pub fn new(name: String, monitor_ids: Vec<String>) -> ProfileMonitorsNotPresentError {
+        ProfileMonitorsNotPresentError { name, monitor_ids }
+    }
+}
+impl std::error::Error for ProfileMonitorsNotPresentError {}
+impl std::fmt::Display for ProfileMonitorsNotPresentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Profile {:?} is missing monitors {:?}!",
+            self.name, self.monitor_ids
+        )
+    }
+}
+
+// Collects all of the errors that can occur when selecting profile.
+#[derive(Debug)]
+pub(crate) enum SelectProfileError {
+    ProfileNotFoundError(ProfileNotFoundError),
+    ProfileMonitorsNotPresentError(ProfileMonitorsNotPresentError),
+}
+impl std::fmt::Display for SelectProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SelectProfileError::ProfileNotFoundError(e) => {
+                write!(f, "Profile not found error:\n{}", e)
+            }
+            SelectProfileError::ProfileMonitorsNotPresentError(e) => {
+                write!(f, "Profile monitors not present error:\n{}", e)
+            }
+        }
+    }
+}
+impl From<ProfileNotFoundError> for SelectProfileError {
+    fn from(err: ProfileNotFoundError) -> Self {
+        SelectProfileError::ProfileNotFoundError(err)
+    }
+}
+impl From<ProfileMonitorsNotPresentError> for SelectProfileError {
+    fn from(err: ProfileMonitorsNotPresentError) -> Self {
+        SelectProfileError::ProfileMonitorsNotPresentError(err)
+    }
+}
+
+// Collects all of the errors that can occur when loading config.
+#[derive(Debug)]
+pub(crate) enum ConfigLoadError {
+    IoError(std::io::Error),
+    TomlDeserializeError(toml::de::Error),
+    NoMonitorGroupsGivenError(NoMonitorGroupsGivenError),
+    MirrorMonitorNotInGroupError(MirrorMonitorNotInGroupError),
+    DuplicateProfileNameError(DuplicateProfileNameError),
+    ReservedProfileNameError(ReservedProfileNameError),
+}
+impl std::fmt::Display for ConfigLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigLoadError::IoError(e) => write!(f, "IO error:\n{}", e),
+            ConfigLoadError::TomlDeserializeError(e) => write!(f, "Toml deserialize error:\n{}", e),
+            ConfigLoadError::NoMonitorGroupsGivenError(e) => {
+                write!(f, "No monitor groups given error:\n{}", e)
+            }
+            ConfigLoadError::MirrorMonitorNotInGroupError(e) => {
+                write!(f, "Mirror monitor not in group error:\n{}", e)
+            }
+            ConfigLoadError::DuplicateProfileNameError(e) => {
+                write!(f, "Duplicate profile name error:\n{}", e)
+            }
+            ConfigLoadError::ReservedProfileNameError(e) => {
+                write!(f, "Reserved profile name error:\n{}", e)
+            }
+        }
+    }
+}
+impl From<std::io::Error> for ConfigLoadError {
+    fn from(err: std::io::Error) -> Self {
+        ConfigLoadError::IoError(err)
+    }
+}
+impl From<toml::de::Error> for ConfigLoadError {
+    fn from(err: toml::de::Error) -> Self {
+        ConfigLoadError::TomlDeserializeError(err)
+    }
+}
+impl From<NoMonitorGroupsGivenError> for ConfigLoadError {
+    fn from(err: NoMonitorGroupsGivenError) -> Self {
+        ConfigLoadError::NoMonitorGroupsGivenError(err)
+    }
+}
+impl From<MirrorMonitorNotInGroupError> for ConfigLoadError {
+    fn from(err: MirrorMonitorNotInGroupError) -> Self {
+        ConfigLoadError::MirrorMonitorNotInGroupError(err)
+    }
+}
+impl From<DuplicateProfileNameError> for ConfigLoadError {
+    fn from(err: DuplicateProfileNameError) -> Self {
+        ConfigLoadError::DuplicateProfileNameError(err)
+    }
+}
+impl From<ReservedProfileNameError> for ConfigLoadError {
+    fn from(err: ReservedProfileNameError) -> Self {
+        ConfigLoadError::ReservedProfileNameError(err)
+    }
+}
+
+// Lock held error.
+#[derive(Debug)]
+pub(crate) struct LockHeldError {
+    mode: String,
+}
+impl LockHeldError {
+    pub fn new(mode: String) -> LockHeldError {
+        LockHeldError { mode }
+    }
+}
+impl std::error::Error for LockHeldError {}
+impl std::fmt::Display for LockHeldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Another instance in {:?} mode is already running for this display!",
+            self.mode
+        )
+    }
+}
+
+// Collects all of the errors that can occur when taking lock.
+#[derive(Debug)]
+pub(crate) enum LockError {
+    ReplyOrIdError(x11rb::errors::ReplyOrIdError),
+    LockHeldError(LockHeldError),
+}
+impl std::fmt::Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LockError::ReplyOrIdError(e) => write!(f, "Reply or id error:\n{}", e),
+            LockError::LockHeldError(e) => write!(f, "Lock held error:\n{}", e),
+        }
+    }
+}
+impl From<x11rb::errors::ReplyOrIdError> for LockError {
+    fn from(err: x11rb::errors::ReplyOrIdError) -> Self {
+        LockError::ReplyOrIdError(err)
+    }
+}
+impl From<LockHeldError> for LockError {
+    fn from(err: LockHeldError) -> Self {
+        LockError::LockHeldError(err)
+    }
+}
+
+// No watch mode running error.
+#[derive(Debug)]
+pub(crate) struct NoWatchRunningError {}
+impl NoWatchRunningError {
+    pub fn new() -> NoWatchRunningError {
+        NoWatchRunningError {}
+    }
+}
+impl std::error::Error for NoWatchRunningError {}
+impl std::fmt::Display for NoWatchRunningError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Watch mode is not running for this display!")
+    }
+}
+
+// Delegate refused error.
+#[derive(Debug)]
+pub(crate) struct DelegateRefusedError {
+    reason: String,
+}
+impl DelegateRefusedError {
+    pub fn new(reason: String) -> DelegateRefusedError {
+        DelegateRefusedError { reason }
+    }
+}
+impl std::error::Error for DelegateRefusedError {}
+impl std::fmt::Display for DelegateRefusedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Watch mode is running and applies monitors, but {}!",
+            self.reason
+        )
+    }
+}
+
+// Control failed error.
+#[derive(Debug)]
+pub(crate) struct ControlFailedError {
+    error: String,
+}
+impl ControlFailedError {
+    pub fn new(error: String) -> ControlFailedError {
+        ControlFailedError { error }
+    }
+}
+impl std::error::Error for ControlFailedError {}
+impl std::fmt::Display for ControlFailedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Watch mode failed request:\n{}", self.error)
+    }
+}
+
+// No runtime directory error.
+#[derive(Debug)]
+pub(crate) struct NoRuntimeDirError {}
+impl NoRuntimeDirError {
+    pub fn new() -> NoRuntimeDirError {
+        NoRuntimeDirError {}
+    }
+}
+impl std::error::Error for NoRuntimeDirError {}
+impl std::fmt::Display for NoRuntimeDirError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "$XDG_RUNTIME_DIR is not set, so there is no control socket!"
+        )
+    }
+}
+
+// Control timed out error.
+#[derive(Debug)]
+pub(crate) struct ControlTimedOutError {
+    timeout: std::time::Duration,
+}
+impl ControlTimedOutError {
+    pub fn new(timeout: std::time::Duration) -> ControlTimedOutError {
+        ControlTimedOutError { timeout }
+    }
+}
+impl std::error::Error for ControlTimedOutError {}
+impl std::fmt::Display for ControlTimedOutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Watch mode did not respond in {} seconds!",
+            self.timeout.as_secs_f64()
+        )
+    }
+}
+
+// Collects all of the errors that can occur when controlling watch mode.
+#[derive(Debug)]
+pub(crate) enum ControlError {
+    IoError(std::io::Error),
+    SerdeJsonError(serde_json::Error),
+    NoWatchRunningError(NoWatchRunningError),
+    ControlFailedError(ControlFailedError),
+    NoRuntimeDirError(NoRuntimeDirError),
+    ControlTimedOutError(ControlTimedOutError),
+    DelegateRefusedError(DelegateRefusedError),
+}
+impl std::fmt::Display for ControlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ControlError::IoError(e) => write!(f, "IO error:\n{}", e),
+            ControlError::SerdeJsonError(e) => write!(f, "Serde JSON error:\n{}", e),
+            ControlError::NoWatchRunningError(e) => write!(f, "No watch running error:\n{}", e),
+            ControlError::ControlFailedError(e) => write!(f, "Control failed error:\n{}", e),
+            ControlError::NoRuntimeDirError(e) => write!(f, "No runtime dir error:\n{}", e),
+            ControlError::ControlTimedOutError(e) => {
+                write!(f, "Control timed out error:\n{}", e)
+            }
+            ControlError::DelegateRefusedError(e) => {
+                write!(f, "Delegate refused error:\n{}", e)
+            }
+        }
+    }
+}
+impl From<std::io::Error> for ControlError {
+    fn from(err: std::io::Error) -> Self {
+        ControlError::IoError(err)
+    }
+}
+impl From<serde_json::Error> for ControlError {
+    fn from(err: serde_json::Error) -> Self {
+        ControlError::SerdeJsonError(err)
+    }
+}
+impl From<NoWatchRunningError> for ControlError {
+    fn from(err: NoWatchRunningError) -> Self {
+        ControlError::NoWatchRunningError(err)
+    }
+}
+impl From<ControlFailedError> for ControlError {
+    fn from(err: ControlFailedError) -> Self {
+        ControlError::ControlFailedError(err)
+    }
+}
+impl From<DelegateRefusedError> for ControlError {
+    fn from(err: DelegateRefusedError) -> Self {
+        ControlError::DelegateRefusedError(err)
+    }
+}
+impl From<NoRuntimeDirError> for ControlError {
+    fn from(err: NoRuntimeDirError) -> Self {
+        ControlError::NoRuntimeDirError(err)
+    }
+}
+impl From<ControlTimedOutError> for ControlError {
+    fn from(err: ControlTimedOutError) -> Self {
+        ControlError::ControlTimedOutError(err)
+    }
+}
+
+// Hook failed error.
+#[derive(Debug)]
+pub(crate) struct HookFailedError {
+    command: String,
+    status: std::process::ExitStatus,
+}
+impl HookFailedError {
+    pub fn new(command: String, status: std::process::ExitStatus) -> HookFailedError {
+        HookFailedError { command, status }
+    }
+}
+impl std::error::Error for HookFailedError {}
+impl std::fmt::Display for HookFailedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Hook {:?} failed with {}!", self.command, self.status)
+    }
+}
+
+// Hook timed out error.
+#[derive(Debug)]
+pub(crate) struct HookTimedOutError {
+    command: String,
+    timeout: std::time::Duration,
+}
+impl HookTimedOutError {
+    pub fn new(command: String, timeout: std::time::Duration) -> HookTimedOutError {
+        HookTimedOutError { command, timeout }
+    }
+}
+impl std::error::Error for HookTimedOutError {}
+impl std::fmt::Display for HookTimedOutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Hook {:?} did not finish in {:?} and was killed!",
+            self.command, self.timeout
+        )
+    }
+}
+
+// Collects all of the errors that can occur when running hook.
+#[derive(Debug)]
+pub(crate) enum HookError {
+    IoError(std::io::Error),
+    HookFailedError(HookFailedError),
+    HookTimedOutError(HookTimedOutError),
+}
+impl std::fmt::Display for HookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HookError::IoError(e) => write!(f, "IO error:\n{}", e),
+            HookError::HookFailedError(e) => write!(f, "Hook failed error:\n{}", e),
+            HookError::HookTimedOutError(e) => write!(f, "Hook timed out error:\n{}", e),
+        }
+    }
+}
+impl From<std::io::Error> for HookError {
+    fn from(err: std::io::Error) -> Self {
+        HookError::IoError(err)
+    }
+}
+impl From<HookFailedError> for HookError {
+    fn from(err: HookFailedError) -> Self {
+        HookError::HookFailedError(err)
+    }
+}
+impl From<HookTimedOutError> for HookError {
+    fn from(err: HookTimedOutError) -> Self {
+        HookError::HookTimedOutError(err)
+    }
+}
+
+// Collects all of the errors that can occur when creating plan.
+#[derive(Debug)]
+pub(crate) enum PlanNewError {
+    ReplyError(x11rb::errors::ReplyError),
+    MonitorNotFoundError(MonitorNotFoundError),
+    MonitorModeInfoError(MonitorModeInfoError),
+    ScreenSizeTooLargeError(ScreenSizeTooLargeError),
+    NoCrtcAssignmentError(NoCrtcAssignmentError),
+}
+impl std::fmt::Display for PlanNewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlanNewError::ReplyError(e) => write!(f, "Reply error:\n{}", e),
+            PlanNewError::MonitorNotFoundError(e) => {
+                write!(f, "Monitor not found error:\n{}", e)
+            }
+            PlanNewError::MonitorModeInfoError(e) => write!(f, "Monitor mode info error:\n{}", e),
+            PlanNewError::ScreenSizeTooLargeError(e) => {
+                write!(f, "Screen size too large error:\n{}", e)
+            }
+            PlanNewError::NoCrtcAssignmentError(e) => write!(f, "No CRTC assignment error:\n{}", e),
+        }
+    }
+}
+impl From<x11rb::errors::ReplyError> for PlanNewError {
+    fn from(err: x11rb::errors::ReplyError) -> Self {
+        PlanNewError::ReplyError(err)
+    }
+}
+impl From<MonitorNotFoundError> for PlanNewError {
+    fn from(err: MonitorNotFoundError) -> Self {
+        PlanNewError::MonitorNotFoundError(err)
+    }
+}
+impl From<MonitorModeInfoError> for PlanNewError {
+    fn from(err: MonitorModeInfoError) -> Self {
+        PlanNewError::MonitorModeInfoError(err)
+    }
+}
+impl From<ScreenSizeTooLargeError> for PlanNewError {
+    fn from(err: ScreenSizeTooLargeError) -> Self {
+        PlanNewError::ScreenSizeTooLargeError(err)
+    }
+}
+impl From<NoCrtcAssignmentError> for PlanNewError {
+    fn from(err: NoCrtcAssignmentError) -> Self {
+        PlanNewError::NoCrtcAssignmentError(err)
+    }
+}
+
+// Collects all of the errors that can occur when applying steps of plan.
+#[derive(Debug)]
+pub(crate) enum PlanApplyStepsError {
+    ReplyError(x11rb::errors::ReplyError),
+    SetCrtcConfigError(SetCrtcConfigError),
+}
+impl std::fmt::Display for PlanApplyStepsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlanApplyStepsError::ReplyError(e) => write!(f, "Reply error:\n{}", e),
+            PlanApplyStepsError::SetCrtcConfigError(e) => {
+                write!(f, "Set CRTC config error:\n{}", e)
+            }
+        }
+    }
+}
+impl From<x11rb::errors::ReplyError> for PlanApplyStepsError {
+    fn from(err: x11rb::errors::ReplyError) -> Self {
+        PlanApplyStepsError::ReplyError(err)
+    }
+}
+impl From<SetCrtcConfigError> for PlanApplyStepsError {
+    fn from(err: SetCrtcConfigError) -> Self {
+        PlanApplyStepsError::SetCrtcConfigError(err)
+    }
+}
+
+// Rollback error, when applying failed and previous configuration was restored.
+#[derive(Debug)]
+pub(crate) struct RollbackError {
+    plan_apply_steps_error: PlanApplyStepsError,
+    restore_error: Option<SetCrtcConfigError>,
+}
+impl RollbackError {
+    pub fn new(
+        plan_apply_steps_error: PlanApplyStepsError,
+        restore_error: Option<SetCrtcConfigError>,
+    ) -> RollbackError {
+        RollbackError {
+            plan_apply_steps_error,
+            restore_error,
+        }
+    }
+}
+impl std::error::Error for RollbackError {}
+impl std::fmt::Display for RollbackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Applying plan failed:\n{}\n{}",
+            self.plan_apply_steps_error,
+            match &self.restore_error {
+                None => "Previous configuration was restored.".to_string(),
+                Some(restore_error) => format!(
+                    "Restoring previous configuration also failed:\n{}",
+                    restore_error
+                ),
+            }
+        )
+    }
+}
+
+// Collects all of the errors that can occur when applying plan.
+#[derive(Debug)]
+pub(crate) enum PlanApplyError {
+    ReplyError(x11rb::errors::ReplyError),
+    RollbackError(RollbackError),
+}
+impl std::fmt::Display for PlanApplyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlanApplyError::ReplyError(e) => write!(f, "Reply error:\n{}", e),
+            PlanApplyError::RollbackError(e) => write!(f, "Rollback error:\n{}", e),
+        }
+    }
+}
+impl From<x11rb::errors::ReplyError> for PlanApplyError {
+    fn from(err: x11rb::errors::ReplyError) -> Self {
+        PlanApplyError::ReplyError(err)
+    }
+}
+impl From<RollbackError> for PlanApplyError {
+    fn from(err: RollbackError) -> Self {
+        PlanApplyError::RollbackError(err)
+    }
+}
+
 // Collects all of the errors that can occur when running main.
 #[derive(Debug)]
 pub(crate) enum X11EDIDAutoError {
     InvalidArgumentsError(InvalidArgumentsError),
     IoError(std::io::Error),
-    TomlDeserializeError(toml::de::Error),
-    NoMonitorGroupsGivenError(NoMonitorGroupsGivenError),
     ConnectionNewError(ConnectionNewError),
     ReplyError(x11rb::errors::ReplyError),
     NoMonitorsFoundError(NoMonitorsFoundError),
-    MonitorNotFoundError(MonitorNotFoundError),
-    MonitorEnableError(MonitorEnableError),
+    PlanNewError(PlanNewError),
+    PlanApplyError(PlanApplyError),
     ConnectionError(x11rb::errors::ConnectionError),
-    MonitorModeInfoError(MonitorModeInfoError),
-    TryIntoI16Error(TryIntoI16Error),
-    ConnectionEndError(ConnectionEndError),
     NoMonitorGroupWithAllMonitorsPresentError(NoMonitorGroupWithAllMonitorsPresentError),
+    SelectProfileError(SelectProfileError),
+    MonitorNameError(MonitorNameError),
+    ConfigLoadError(ConfigLoadError),
+    LockError(LockError),
+    ControlError(ControlError),
 }
 impl std::fmt::Display for X11EDIDAutoError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -431,27 +988,14 @@ impl std::fmt::Display for X11EDIDAutoError {
                 write!(f, "Invalid arguments error:\n{}", e)
             }
             X11EDIDAutoError::IoError(e) => write!(f, "IO error:\n{}", e),
-            X11EDIDAutoError::TomlDeserializeError(e) => {
-                write!(f, "Toml deserialize error:\n{}", e)
-            }
-            X11EDIDAutoError::NoMonitorGroupsGivenError(e) => {
-                write!(f, "No monitor groups given error:\n{}", e)
-            }
             X11EDIDAutoError::ConnectionNewError(e) => write!(f, "Connection new error:\n{}", e),
             X11EDIDAutoError::ReplyError(e) => write!(f, "Reply error:\n{}", e),
             X11EDIDAutoError::NoMonitorsFoundError(e) => {
                 write!(f, "No monitors found error:\n{}", e)
             }
-            X11EDIDAutoError::MonitorNotFoundError(e) => {
-                write!(f, "Monitor not found error:\n{}", e)
-            }
-            X11EDIDAutoError::MonitorEnableError(e) => write!(f, "Monitor enable error:\n{}", e),
+            X11EDIDAutoError::PlanNewError(e) => write!(f, "Plan new error:\n{}", e),
+            X11EDIDAutoError::PlanApplyError(e) => write!(f, "Plan apply error:\n{}", e),
             X11EDIDAutoError::ConnectionError(e) => write!(f, "Connection error:\n{}", e),
-            X11EDIDAutoError::MonitorModeInfoError(e) => {
-                write!(f, "Monitor mode info error:\n{}", e)
-            }
-            X11EDIDAutoError::TryIntoI16Error(e) => write!(f, "Try into i16 error:\n{}", e),
-            X11EDIDAutoError::ConnectionEndError(e) => write!(f, "Connection end error:\n{}", e),
             X11EDIDAutoError::NoMonitorGroupWithAllMonitorsPresentError(e) => {
                 write!(
                     f,
@@ -459,6 +1003,11 @@ impl std::fmt::Display for X11EDIDAutoError {
                     e
                 )
             }
+            X11EDIDAutoError::SelectProfileError(e) => write!(f, "Select profile error:\n{}", e),
+            X11EDIDAutoError::MonitorNameError(e) => write!(f, "Monitor name error:\n{}", e),
+            X11EDIDAutoError::ConfigLoadError(e) => write!(f, "Config load error:\n{}", e),
+            X11EDIDAutoError::LockError(e) => write!(f, "Lock error:\n{}", e),
+            X11EDIDAutoError::ControlError(e) => write!(f, "Control error:\n{}", e),
         }
     }
 }
@@ -472,16 +1021,6 @@ impl From<std::io::Error> for X11EDIDAutoError {
         X11EDIDAutoError::IoError(err)
     }
 }
-impl From<toml::de::Error> for X11EDIDAutoError {
-    fn from(err: toml::de::Error) -> Self {
-        X11EDIDAutoError::TomlDeserializeError(err)
-    }
-}
-impl From<NoMonitorGroupsGivenError> for X11EDIDAutoError {
-    fn from(err: NoMonitorGroupsGivenError) -> Self {
-        X11EDIDAutoError::NoMonitorGroupsGivenError(err)
-    }
-}
 impl From<ConnectionNewError> for X11EDIDAutoError {
     fn from(err: ConnectionNewError) -> Self {
         X11EDIDAutoError::ConnectionNewError(err)
@@ -497,14 +1036,14 @@ impl From<NoMonitorsFoundError> for X11EDIDAutoError {
         X11EDIDAutoError::NoMonitorsFoundError(err)
     }
 }
-impl From<MonitorNotFoundError> for X11EDIDAutoError {
-    fn from(err: MonitorNotFoundError) -> Self {
-        X11EDIDAutoError::MonitorNotFoundError(err)
+impl From<PlanNewError> for X11EDIDAutoError {
+    fn from(err: PlanNewError) -> Self {
+        X11EDIDAutoError::PlanNewError(err)
     }
 }
-impl From<MonitorEnableError> for X11EDIDAutoError {
-    fn from(err: MonitorEnableError) -> Self {
-        X11EDIDAutoError::MonitorEnableError(err)
+impl From<PlanApplyError> for X11EDIDAutoError {
+    fn from(err: PlanApplyError) -> Self {
+        X11EDIDAutoError::PlanApplyError(err)
     }
 }
 impl From<x11rb::errors::ConnectionError> for X11EDIDAutoError {
@@ -512,23 +1051,33 @@ impl From<x11rb::errors::ConnectionError> for X11EDIDAutoError {
         X11EDIDAutoError::ConnectionError(err)
     }
 }
-impl From<MonitorModeInfoError> for X11EDIDAutoError {
-    fn from(err: MonitorModeInfoError) -> Self {
-        X11EDIDAutoError::MonitorModeInfoError(err)
+impl From<NoMonitorGroupWithAllMonitorsPresentError> for X11EDIDAutoError {
+    fn from(err: NoMonitorGroupWithAllMonitorsPresentError) -> Self {
+        X11EDIDAutoError::NoMonitorGroupWithAllMonitorsPresentError(err)
     }
 }
-impl From<TryIntoI16Error> for X11EDIDAutoError {
-    fn from(err: TryIntoI16Error) -> Self {
-        X11EDIDAutoError::TryIntoI16Error(err)
+impl From<SelectProfileError> for X11EDIDAutoError {
+    fn from(err: SelectProfileError) -> Self {
+        X11EDIDAutoError::SelectProfileError(err)
     }
 }
-impl From<ConnectionEndError> for X11EDIDAutoError {
-    fn from(err: ConnectionEndError) -> Self {
-        X11EDIDAutoError::ConnectionEndError(err)
+impl From<MonitorNameError> for X11EDIDAutoError {
+    fn from(err: MonitorNameError) -> Self {
+        X11EDIDAutoError::MonitorNameError(err)
     }
 }
-impl From<NoMonitorGroupWithAllMonitorsPresentError> for X11EDIDAutoError {
-    fn from(err: NoMonitorGroupWithAllMonitorsPresentError) -> Self {
-        X11EDIDAutoError::NoMonitorGroupWithAllMonitorsPresentError(err)
+impl From<ConfigLoadError> for X11EDIDAutoError {
+    fn from(err: ConfigLoadError) -> Self {
+        X11EDIDAutoError::ConfigLoadError(err)
+    }
+}
+impl From<LockError> for X11EDIDAutoError {
+    fn from(err: LockError) -> Self {
+        X11EDIDAutoError::LockError(err)
+    }
+}
+impl From<ControlError> for X11EDIDAutoError {
+    fn from(err: ControlError) -> Self {
+        X11EDIDAutoError::ControlError(err)
     }
 }
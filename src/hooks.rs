@@ -0,0 +1,229 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Seconds before hook command is killed, when not given in config.
+const DEFAULT_TIMEOUT: f64 = 10.0;
+
+// How often to check whether hook command has finished.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+// Points where hook commands are run.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Hook {
+    // Before applying monitor group.
+    PreApply,
+    // After monitor group was applied.
+    PostApply,
+    // When no monitor group matches connected monitors.
+    OnNoMatch,
+    // When applying monitor group failed.
+    OnError,
+}
+
+// Methods for hook.
+impl Hook {
+    // Name of hook in config.
+    fn name(self) -> &'static str {
+        match self {
+            Hook::PreApply => "pre_apply",
+            Hook::PostApply => "post_apply",
+            Hook::OnNoMatch => "on_no_match",
+            Hook::OnError => "on_error",
+        }
+    }
+
+    // Command for hook from hooks.
+    fn command(self, hooks: &crate::config::Hooks) -> Option<&str> {
+        match self {
+            Hook::PreApply => hooks.pre_apply.as_deref(),
+            Hook::PostApply => hooks.post_apply.as_deref(),
+            Hook::OnNoMatch => hooks.on_no_match.as_deref(),
+            Hook::OnError => hooks.on_error.as_deref(),
+        }
+    }
+}
+
+// Run commands for hook from config and from monitor group, with environment.
+// Failing commands are only reported.
+pub(crate) fn run(
+    config: &crate::config::Config,
+    monitor_group: Option<(usize, &crate::config::MonitorGroup)>,
+    hook: Hook,
+    environment: &[(String, String)],
+) {
+    let environment: Vec<(String, String)> = hook_environment(monitor_group, hook, environment);
+
+    let mut hooks: Vec<&crate::config::Hooks> = vec![&config.hooks];
+    if let Some((_, monitor_group)) = monitor_group {
+        hooks.push(&monitor_group.hooks);
+    }
+    for hooks in hooks {
+        if let Some(command) = hook.command(hooks) {
+            println!("Running {} hook {:?}...", hook.name(), command);
+            if let Err(error) = run_command(command, &environment, timeout(config, hooks)) {
+                crate::log::error(&error);
+            }
+        }
+    }
+}
+
+// Environment for hook, with its name and monitor group's key.
+fn hook_environment(
+    monitor_group: Option<(usize, &crate::config::MonitorGroup)>,
+    hook: Hook,
+    environment: &[(String, String)],
+) -> Vec<(String, String)> {
+    let mut environment: Vec<(String, String)> = environment.to_vec();
+    environment.push(("X11_EDID_AUTO_HOOK".to_string(), hook.name().to_string()));
+    if let Some((index, monitor_group)) = monitor_group {
+        environment.push((
+            "X11_EDID_AUTO_PROFILE".to_string(),
+            monitor_group.key(index),
+        ));
+    }
+    environment
+}
+
+// Timeout for commands of hooks, the one from config when they have none.
+fn timeout(config: &crate::config::Config, hooks: &crate::config::Hooks) -> std::time::Duration {
+    let timeout: f64 = hooks
+        .timeout
+        .or(config.hooks.timeout)
+        .unwrap_or(DEFAULT_TIMEOUT);
+    std::time::Duration::try_from_secs_f64(timeout).unwrap_or_default()
+}
+
+// Run command with shell, killing it after timeout.
+fn run_command(
+    command: &str,
+    environment: &[(String, String)],
+    timeout: std::time::Duration,
+) -> Result<(), crate::errors::HookError> {
+    let mut child: std::process::Child = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(environment.iter().map(|(name, value)| (name, value)))
+        .stdin(std::process::Stdio::null())
+        .spawn()?;
+    let started: std::time::Instant = std::time::Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if !status.success() {
+                return Err(crate::errors::HookError::HookFailedError(
+                    crate::errors::HookFailedError::new(command.to_string(), status),
+                ));
+            }
+            return Ok(());
+        }
+        if started.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Err(crate::errors::HookError::HookTimedOutError(
+                crate::errors::HookTimedOutError::new(command.to_string(), timeout),
+            ));
+        }
+        // Hooks can run longer than systemd waits for watchdog.
+        crate::systemd::keep_alive();
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Config with hooks from TOML.
+    fn config(toml: &str) -> crate::config::Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn environment_has_hook_and_profile() {
+        let config: crate::config::Config = config(
+            r#"
+            profiles = [{ name = "desk", monitors = ["A"] }]
+            "#,
+        );
+        let environment: Vec<(String, String)> = super::hook_environment(
+            config.profile("desk"),
+            super::Hook::PostApply,
+            &[("X11_EDID_AUTO_SCREEN".to_string(), "1920x1080".to_string())],
+        );
+        assert_eq!(
+            environment,
+            vec![
+                ("X11_EDID_AUTO_SCREEN".to_string(), "1920x1080".to_string()),
+                ("X11_EDID_AUTO_HOOK".to_string(), "post_apply".to_string()),
+                ("X11_EDID_AUTO_PROFILE".to_string(), "desk".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn environment_without_monitor_group_has_no_profile() {
+        let environment: Vec<(String, String)> =
+            super::hook_environment(None, super::Hook::OnNoMatch, &[]);
+        assert_eq!(
+            environment,
+            vec![("X11_EDID_AUTO_HOOK".to_string(), "on_no_match".to_string())]
+        );
+    }
+
+    #[test]
+    fn timeout_falls_back_to_config() {
+        let config: crate::config::Config = config(
+            r#"
+            monitor_groups = [["A"], { monitors = ["B"], hooks = { timeout = 0.5 } }]
+            hooks = { timeout = 2 }
+            "#,
+        );
+        let monitor_groups: Vec<&crate::config::MonitorGroup> = config.monitor_groups().collect();
+        assert_eq!(
+            super::timeout(&config, &monitor_groups[0].hooks),
+            std::time::Duration::from_secs(2)
+        );
+        assert_eq!(
+            super::timeout(&config, &monitor_groups[1].hooks),
+            std::time::Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn timeout_has_default() {
+        let config: crate::config::Config = config(r#"monitor_groups = [["A"]]"#);
+        assert_eq!(
+            super::timeout(&config, &config.hooks),
+            std::time::Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn command_gets_environment() {
+        super::run_command(
+            r##"test "$X11_EDID_AUTO_PROFILE" = "#1""##,
+            &[("X11_EDID_AUTO_PROFILE".to_string(), "#1".to_string())],
+            std::time::Duration::from_secs(10),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn failing_command_is_error() {
+        let error: crate::errors::HookError =
+            super::run_command("exit 3", &[], std::time::Duration::from_secs(10)).unwrap_err();
+        assert!(matches!(
+            error,
+            crate::errors::HookError::HookFailedError(_)
+        ));
+    }
+
+    #[test]
+    fn command_is_killed_after_timeout() {
+        let started: std::time::Instant = std::time::Instant::now();
+        let error: crate::errors::HookError =
+            super::run_command("sleep 10", &[], std::time::Duration::from_millis(100)).unwrap_err();
+        assert!(matches!(
+            error,
+            crate::errors::HookError::HookTimedOutError(_)
+        ));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,66 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Mode of instance that holds lock for watch mode.
+pub(crate) const WATCH: &str = "watch";
+
+// Mode of instance that holds lock for applying once.
+pub(crate) const APPLY: &str = "apply";
+
+// How long to wait between tries when waiting for lock.
+const RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+// Result of taking lock.
+pub(crate) enum Locked {
+    // Lock was taken.
+    Acquired,
+    // Watch mode holds lock and will handle applying.
+    HandedOff,
+}
+
+// Take per display lock for given mode, so that only one instance applies at a time.
+// Applying can be handed off to watch mode, when it would apply the same monitor group.
+pub(crate) fn lock(
+    connection: &crate::connection::Connection,
+    policy: crate::config::Lock,
+    mode: &str,
+    can_hand_off: bool,
+) -> Result<Locked, crate::errors::LockError> {
+    let mut waiting: bool = false;
+    loop {
+        let holder: String = match connection.try_lock(mode)? {
+            None => return Ok(Locked::Acquired),
+            Some(holder) => holder,
+        };
+
+        // Running watch mode handles applying,
+        if holder == WATCH {
+            if !can_hand_off {
+                if mode != WATCH {
+                    eprintln!("Watch mode is running, but request could not be delivered to it!");
+                }
+                return Err(crate::errors::LockError::LockHeldError(
+                    crate::errors::LockHeldError::new(holder),
+                ));
+            }
+            println!("Watch mode is running, leaving applying to it!");
+            return Ok(Locked::HandedOff);
+        }
+
+        // otherwise wait for other instance or exit.
+        match policy {
+            crate::config::Lock::Exit => {
+                return Err(crate::errors::LockError::LockHeldError(
+                    crate::errors::LockHeldError::new(holder),
+                ))
+            }
+            crate::config::Lock::Wait => {
+                if !waiting {
+                    println!("Waiting for another instance in {:?} mode...", holder);
+                    waiting = true;
+                }
+                std::thread::sleep(RETRY_INTERVAL);
+            }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Are messages written for journald?
+static JOURNAL: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+// Write messages for journald from now on.
+pub(crate) fn set_journal() {
+    JOURNAL.store(true, std::sync::atomic::Ordering::Relaxed);
+}
+
+// Print error, for journald on one line with error priority.
+pub(crate) fn error(error: impl std::fmt::Display) {
+    if JOURNAL.load(std::sync::atomic::Ordering::Relaxed) {
+        eprintln!(
+            "<3>Got error {}",
+            error.to_string().replace(['\n', '\t'], " ")
+        );
+    } else {
+        eprintln!("Got error {}", error);
+    }
+}
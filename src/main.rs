@@ -4,163 +4,156 @@
 // Errors.
 mod errors;
 
+// Command line arguments.
+mod arguments;
+
+// Configuration.
+mod config;
+
 // Connection struct.
 mod connection;
 
+// Control interface for watch mode.
+mod control;
+
+// D-Bus service for watch mode.
+#[cfg(feature = "dbus")]
+mod dbus;
+
+// CRTC assignment.
+mod crtc_assignment;
+
+// CRTC config struct.
+mod crtc_config;
+
+// Single instance lock.
+mod lock;
+
+// Hook commands.
+mod hooks;
+
+// Logging.
+mod log;
+
 // Monitor struct.
 mod monitor;
 
-// Configuration.
-#[derive(Debug, serde::Deserialize)]
-struct Config {
-    monitor_groups: Vec<Vec<String>>,
-}
+// Applying monitor groups.
+mod apply;
+
+// Snapshot of screen configuration.
+mod snapshot;
+
+// Desktop notifications.
+mod notification;
+
+// Plan for monitor group.
+mod plan;
+
+// Monitor group selection.
+mod selection;
+
+// Integration with systemd.
+mod systemd;
+
+// State between runs.
+mod state;
+
+// Watching for monitor changes.
+mod watch;
 
 fn run() -> Result<(), errors::X11EDIDAutoError> {
     // Get arguments.
-    let args: Vec<String> = std::env::args().collect();
-
-    // If had invalid arguments
-    if args.len() != 2 {
-        // return with error.
-        return Err(errors::X11EDIDAutoError::InvalidArgumentsError(
-            errors::InvalidArgumentsError::new(args[0].clone()),
-        ));
+    let arguments: arguments::Arguments = arguments::Arguments::new()?;
+    if arguments.systemd {
+        log::set_journal();
     }
 
-    // Get config file path from arguments.
-    let config_file_path: &str = args[1].trim();
+    // Let running watch mode handle command, if there is one.
+    if control::delegate(&arguments)? {
+        return Ok(());
+    }
 
-    // Generate config from file.
-    let config: Config = toml::from_str(&std::fs::read_to_string(config_file_path)?)?;
+    // Load config from file given in arguments.
+    let config: config::Config = config::Config::load(&arguments.config_file_path)?;
 
-    // If no monitor groups given
-    if config.monitor_groups.is_empty() {
-        // return with error.
-        return Err(errors::X11EDIDAutoError::NoMonitorGroupsGivenError(
-            errors::NoMonitorGroupsGivenError::new(config_file_path.to_string()),
-        ));
+    // Watch for changes,
+    if let arguments::Command::Watch = arguments.command {
+        return watch::Watcher::new(&arguments, config).watch();
     }
 
-    // Get connection.
-    let connection: connection::Connection = connection::Connection::new()?;
-
-    // Create a map of monitor id to monitor.
-    let mut monitor_map: std::collections::HashMap<String, monitor::Monitor> =
-        std::collections::HashMap::new();
-
-    // Outputs to monitors.
-    println!("Getting monitors...");
-    // Loop outputs.
-    for output in connection.outputs()? {
-        // Create monitor for output.
-        let monitor: monitor::Monitor = monitor::Monitor::new(&connection, output)?;
-        // If monitor has EDID
-        if monitor.has_edid() {
-            // add it to map.
-            monitor_map.insert(monitor.monitor_id(), monitor);
-        }
+    // install service for watching,
+    if let arguments::Command::InstallService = arguments.command {
+        return Ok(systemd::install_service(&arguments)?);
     }
 
-    // If no monitors found
-    if monitor_map.is_empty() {
-        // return with error.
-        return Err(errors::X11EDIDAutoError::NoMonitorsFoundError(
-            errors::NoMonitorsFoundError::new(),
-        ));
+    // or apply once, telling user if it failed.
+    let result: Result<(), errors::X11EDIDAutoError> = apply_once(&arguments, &config);
+    if let Err(error) = &result {
+        if !arguments.dry_run && !arguments.list_profiles {
+            notification::failed(&config, error);
+        }
     }
+    result
+}
 
-    // Loop monitor groups.
-    println!("Monitor groups...");
-    let mut set: bool = false;
-    let mut index = 0;
-    // While not set and still has monitor groups.
-    while index < config.monitor_groups.len() && !set {
-        // Get monitor group.
-        let monitor_group = &config.monitor_groups[index];
-
-        // If all of the groups monitors are connected.
-        if monitor_group
-            .iter()
-            .all(|monitor_edid| monitor_map.contains_key(monitor_edid))
+// Apply monitor group once.
+fn apply_once(
+    arguments: &arguments::Arguments,
+    config: &config::Config,
+) -> Result<(), errors::X11EDIDAutoError> {
+    // Apply with a new connection,
+    let mut connection: connection::Connection = connection::Connection::new()?;
+
+    // while holding lock, unless only looking.
+    if !arguments.dry_run && !arguments.list_profiles {
+        // Watch mode would not apply given profile or cycle.
+        let can_hand_off: bool =
+            matches!(arguments.command, arguments::Command::Apply) && arguments.profile.is_none();
+        if let lock::Locked::HandedOff =
+            lock::lock(&connection, config.lock, lock::APPLY, can_hand_off)?
         {
-            println!(
-                "{:?}. monitor group had all of it's monitors {:?} present!",
-                index + 1,
-                monitor_group
-            );
-
-            // Enable monitors in monitor group.
-            let mut x: i16 = 0;
-            for monitor_edid in monitor_group {
-                let monitor: &monitor::Monitor = match monitor_map.get(monitor_edid) {
-                    Some(monitor) => monitor,
-                    None => {
-                        return Err(errors::X11EDIDAutoError::MonitorNotFoundError(
-                            errors::MonitorNotFoundError::new(monitor_edid.to_string()),
-                        ))
-                    }
-                };
-                monitor.enable(x)?;
-                if x == 0 {
-                    monitor.set_primary()?;
-                }
-                x += std::convert::TryInto::<i16>::try_into(monitor.mode_info()?.width).map_err(
-                    |try_from_int_error: std::num::TryFromIntError| {
-                        errors::TryIntoI16Error::new(
-                            "Monitor width".to_string(),
-                            try_from_int_error,
-                        )
-                    },
-                )?;
-            }
-
-            // Disable monitors that are not in monitor group.
-            println!("Disabling unused monitors...");
-            for (monitor_edid, monitor) in &monitor_map {
-                if !monitor_group.contains(&monitor_edid) {
-                    monitor.disable()?;
-                }
-            }
-
-            // Inform that monitor group was set so can stop looping.
-            set = true;
-        }
-        // If monitor group did not have all of it's monitors present
-        else {
-            // inform user.
-            eprintln!(
-                "{:?}. monitor group did not have all of it's monitors {:?} present!",
-                index + 1,
-                monitor_group
-            );
+            return Ok(());
         }
-
-        // Increment index.
-        index += 1;
     }
 
-    // If did set monitor group
-    if set {
-        // end connection.
-        connection.end()?;
-    // else if did not set any monitor group
-    } else {
-        // return error.
-        return Err(
-            errors::X11EDIDAutoError::NoMonitorGroupWithAllMonitorsPresentError(
-                errors::NoMonitorGroupWithAllMonitorsPresentError::new(
-                    monitor_map
-                        .values()
-                        .map(|monitor| monitor.monitor_info())
-                        .collect(),
-                ),
-            ),
-        );
+    // after waiting for monitors, if asked to.
+    if let Some(wait) = arguments.wait {
+        watch::wait(
+            &mut connection,
+            arguments,
+            config,
+            std::time::Duration::try_from_secs_f64(wait).unwrap_or(std::time::Duration::MAX),
+        )?;
+    }
+    let monitor_map: std::collections::HashMap<String, monitor::Monitor> =
+        apply::monitor_map(&connection, config)?;
+
+    // If only listing profiles
+    if arguments.list_profiles {
+        // print matching ones and stop.
+        selection::print_profiles(&selection::profiles(&selection::matching(
+            config,
+            &monitor_map,
+        )));
+        return Ok(());
     }
-    // Done.
-    println!("Done!");
-    Ok(())
+
+    // Select monitor group and apply it.
+    let monitor_group: (usize, &config::MonitorGroup) = apply::select(
+        config,
+        &monitor_map,
+        &arguments.command,
+        arguments.profile.as_deref(),
+    )
+    .inspect_err(|error| apply::no_match(config, &monitor_map, error, arguments.dry_run))?;
+    apply::apply(
+        &connection,
+        config,
+        &monitor_map,
+        monitor_group,
+        arguments.dry_run,
+    )
 }
 
 // Automatic monitor configuration based on EDID.
@@ -168,7 +161,7 @@ fn main() {
     match run() {
         Ok(()) => std::process::exit(0),
         Err(error) => {
-            eprintln!("Got error {}", error);
+            log::error(&error);
             std::process::exit(1);
         }
     }
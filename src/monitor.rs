@@ -9,6 +9,8 @@ pub(crate) struct Monitor<'a> {
     output: u32,
     // EDID.
     edid: Vec<u8>,
+    // Alias from config.
+    alias: Option<String>,
 }
 
 // Methods for monitor.
@@ -24,14 +26,34 @@ impl<'a> Monitor<'a> {
             connection,
             output,
             edid: connection.edid(output)?,
+            alias: None,
         })
     }
 
+    // Set alias from config.
+    pub(crate) fn with_alias(mut self, alias: Option<String>) -> Self {
+        self.alias = alias;
+        self
+    }
+
+    // Key for monitor in config, alias or monitor ID.
+    pub(crate) fn key(&self) -> String {
+        match &self.alias {
+            Some(alias) => alias.clone(),
+            None => self.monitor_id(),
+        }
+    }
+
     // Has EDID?
     pub(crate) fn has_edid(&self) -> bool {
         !self.edid.is_empty()
     }
 
+    // Has alias from config?
+    pub(crate) fn has_alias(&self) -> bool {
+        self.alias.is_some()
+    }
+
     // Manifacturer ID.
     fn manufacturer_id(&self) -> u16 {
         u16::from_be_bytes([self.edid[8], self.edid[9]])
@@ -49,6 +71,10 @@ impl<'a> Monitor<'a> {
 
     // Monitor ID.
     pub(crate) fn monitor_id(&self) -> String {
+        // Monitors without EDID do not have one.
+        if !self.has_edid() {
+            return String::new();
+        }
         format!(
             "{:04X}:{:04X}:{:08X}",
             self.manufacturer_id(),
@@ -57,6 +83,91 @@ impl<'a> Monitor<'a> {
         )
     }
 
+    // Fingerprint of the whole EDID, FNV-1a hash.
+    pub(crate) fn fingerprint(&self) -> String {
+        let hash: u64 = self.edid.iter().fold(0xCBF29CE484222325, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x100000001B3)
+        });
+        format!("{:016X}", hash)
+    }
+
+    // Monitor name from EDID display name descriptor.
+    pub(crate) fn edid_name(&self) -> Option<String> {
+        // Four 18 byte descriptors start at byte 54.
+        for start in [54, 72, 90, 108] {
+            let descriptor: &[u8] = self.edid.get(start..start + 18)?;
+            // Display name descriptor has tag 0xFC.
+            if descriptor[0..3] == [0, 0, 0] && descriptor[3] == 0xFC {
+                let name: &[u8] = &descriptor[5..];
+                let end: usize = name.iter().position(|byte| *byte == 0x0A).unwrap_or(13);
+                return Some(String::from_utf8_lossy(&name[..end]).trim().to_string());
+            }
+        }
+        None
+    }
+
+    // Does monitor match all of the given criteria?
+    pub(crate) fn matches(
+        &self,
+        criteria: &crate::config::MonitorCriteria,
+    ) -> Result<bool, crate::errors::MonitorNameError> {
+        if criteria.is_empty() {
+            return Ok(false);
+        }
+        if let Some(edid) = &criteria.edid {
+            if *edid != self.monitor_id() {
+                return Ok(false);
+            }
+        }
+        if let Some(fingerprint) = &criteria.fingerprint {
+            if !fingerprint.eq_ignore_ascii_case(&self.fingerprint()) {
+                return Ok(false);
+            }
+        }
+        if let Some(name) = &criteria.name {
+            if Some(name) != self.edid_name().as_ref() {
+                return Ok(false);
+            }
+        }
+        if let Some(connector) = &criteria.connector {
+            if !glob_matches(connector.as_bytes(), self.name()?.as_bytes()) {
+                return Ok(false);
+            }
+        }
+        if let Some(connector_type) = &criteria.connector_type {
+            if !self
+                .connector_type()?
+                .is_some_and(|own| own.eq_ignore_ascii_case(connector_type))
+            {
+                return Ok(false);
+            }
+        }
+        if let Some(internal) = criteria.internal {
+            if internal != self.is_internal()? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // Connector type of monitor's output, if driver tells it.
+    pub(crate) fn connector_type(&self) -> Result<Option<String>, x11rb::errors::ReplyError> {
+        self.connection.connector_type(self.output)
+    }
+
+    // Is monitor internal laptop panel?
+    pub(crate) fn is_internal(&self) -> Result<bool, crate::errors::MonitorNameError> {
+        // Driver tells it with connector type,
+        if let Some(connector_type) = self.connector_type()? {
+            return Ok(connector_type == "Panel");
+        }
+        // or it is guessed from output name.
+        let name: String = self.name()?.to_uppercase();
+        Ok(["EDP", "LVDS", "DSI"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix)))
+    }
+
     // Get output info for monitor's output.
     fn output_info(
         &self,
@@ -65,90 +176,167 @@ impl<'a> Monitor<'a> {
     }
 
     // Name for monitor's output.
-    fn name(&self) -> Result<String, crate::errors::MonitorNameError> {
+    pub(crate) fn name(&self) -> Result<String, crate::errors::MonitorNameError> {
         Ok(String::from_utf8(self.output_info()?.name.to_vec())?)
     }
 
+    // Connection status of monitor's output.
+    pub(crate) fn connection_status(
+        &self,
+    ) -> Result<x11rb::protocol::randr::Connection, x11rb::errors::ReplyError> {
+        Ok(self.output_info()?.connection)
+    }
+
+    // Is monitor's output connected, or is its status unknown when those are included?
+    pub(crate) fn is_connected(
+        &self,
+        include_unknown: bool,
+    ) -> Result<bool, x11rb::errors::ReplyError> {
+        Ok(is_connected(self.connection_status()?, include_unknown))
+    }
+
+    // Output id.
+    pub(crate) fn output(&self) -> u32 {
+        self.output
+    }
+
+    // CRTCs that can drive monitor's output.
+    pub(crate) fn possible_crtcs(&self) -> Result<Vec<u32>, x11rb::errors::ReplyError> {
+        Ok(self.output_info()?.crtcs)
+    }
+
     // CRTC for monitor's output.
-    fn crtc(&self) -> Result<u32, x11rb::errors::ReplyError> {
+    pub(crate) fn crtc(&self) -> Result<u32, x11rb::errors::ReplyError> {
         Ok(self.output_info()?.crtc)
     }
 
     pub(crate) fn monitor_info(&self) -> Vec<String> {
-        vec![
-            format!("edid: {:?}", self.monitor_id()),
-            format!("name: {:?}", self.name().unwrap()),
-            format!("crtc: {:?}", self.crtc().unwrap()),
-        ]
+        let mut monitor_info: Vec<String> = Vec::new();
+        if let Some(alias) = &self.alias {
+            monitor_info.push(format!("alias: {:?}", alias));
+        }
+        if self.has_edid() {
+            monitor_info.push(format!("edid: {:?}", self.monitor_id()));
+            monitor_info.push(format!("fingerprint: {:?}", self.fingerprint()));
+        } else {
+            monitor_info.push("edid: none".to_string());
+        }
+        if let Some(edid_name) = self.edid_name() {
+            monitor_info.push(format!("edid name: {:?}", edid_name));
+        }
+        // Output might disappear meanwhile, so errors are shown instead.
+        monitor_info.push(info("name", self.name()));
+        monitor_info.push(info("status", self.connection_status()));
+        match self.connector_type() {
+            Ok(Some(connector_type)) => {
+                monitor_info.push(format!("connector type: {:?}", connector_type))
+            }
+            Ok(None) => {}
+            Err(error) => monitor_info.push(info::<()>("connector type", Err(error))),
+        }
+        monitor_info.push(info("crtc", self.crtc()));
+        monitor_info
     }
 
     // Print monitor information.
-    fn print_monitor(&self) -> &Self {
+    pub(crate) fn print_monitor(&self) -> &Self {
         println!("Monitor:");
         for info in self.monitor_info() {
             println!("\t {}", info);
         }
-        return self;
+        self
+    }
+
+    // Best mode from given modes, the largest one with lowest dot clock.
+    pub(crate) fn best_mode<'m>(
+        modes: impl Iterator<Item = &'m x11rb::protocol::randr::ModeInfo>,
+    ) -> Option<x11rb::protocol::randr::ModeInfo> {
+        modes
+            .max_by(|a, b| {
+                a.width
+                    .cmp(&b.width)
+                    .then_with(|| a.height.cmp(&b.height))
+                    .then_with(|| b.dot_clock.cmp(&a.dot_clock))
+            })
+            .copied()
+    }
+
+    // Get all modes for monitor's output.
+    pub(crate) fn modes(
+        &self,
+    ) -> Result<Vec<x11rb::protocol::randr::ModeInfo>, x11rb::errors::ReplyError> {
+        let mode_info_map: &std::collections::HashMap<u32, x11rb::protocol::randr::ModeInfo> =
+            self.connection.mode_info_map();
+        Ok(self
+            .output_info()?
+            .modes
+            .iter()
+            .filter_map(|mode_id| mode_info_map.get(mode_id)) // Filter modes that exist in mode_info_map
+            .copied()
+            .collect())
     }
 
     // Get mode info for monitor's output.
     pub(crate) fn mode_info(
         &self,
     ) -> Result<x11rb::protocol::randr::ModeInfo, crate::errors::MonitorModeInfoError> {
-        let mode_info_map: &std::collections::HashMap<u32, x11rb::protocol::randr::ModeInfo> =
-            self.connection.mode_info_map();
-        Ok(
-            match self
-                .output_info()?
-                .modes
-                .iter()
-                .filter_map(|mode_id| mode_info_map.get(mode_id)) // Filter modes that exist in mode_info_map
-                .max_by(|a, b| {
-                    a.width
-                        .cmp(&b.width)
-                        .then_with(|| a.height.cmp(&b.height))
-                        .then_with(|| b.dot_clock.cmp(&a.dot_clock))
-                }) {
-                Some(mode_info) => mode_info.clone(),
-                None => {
-                    return Err(crate::errors::MonitorModeInfoError::NoModesError(
-                        crate::errors::NoModesError::new(),
-                    ))
-                }
-            },
-        )
+        match Self::best_mode(self.modes()?.iter()) {
+            Some(mode_info) => Ok(mode_info),
+            None => Err(crate::errors::MonitorModeInfoError::NoModesError(
+                crate::errors::NoModesError::new(),
+            )),
+        }
     }
 
-    // Set CRTC config.
-    pub(crate) fn enable(&self, x: i16) -> Result<&Self, crate::errors::MonitorEnableError> {
-        // Print monitor info.
-        self.print_monitor();
+    // Outputs that can share CRTC with monitor's output.
+    pub(crate) fn clones(&self) -> Result<Vec<u32>, x11rb::errors::ReplyError> {
+        Ok(self.output_info()?.clones)
+    }
 
-        // Get CRTC.
-        let crtc_existing: u32 = self.crtc()?;
-        let crtc: u32;
-        if crtc_existing == 0 {
-            crtc = self.connection.get_free_crtc()?;
-        } else {
-            crtc = crtc_existing;
+    // Physical width in millimeters.
+    fn mm_width(&self) -> Result<u32, x11rb::errors::ReplyError> {
+        let mm_width: u32 = self.output_info()?.mm_width;
+        // If output does not know it, fallback to centimeters from EDID.
+        if mm_width == 0 && self.edid.len() > 21 {
+            return Ok(u32::from(self.edid[21]) * 10);
         }
+        Ok(mm_width)
+    }
 
-        // Set CRTC config.
-        self.connection.set_crtc_config(
-            crtc,
-            x,
-            0,
-            self.mode_info()?.id,
-            x11rb::protocol::randr::Rotation::ROTATE0,
-            &[self.output],
-            crtc_existing,
-        )?;
+    // DPI for given mode, if physical size is known.
+    pub(crate) fn dpi(
+        &self,
+        mode_info: &x11rb::protocol::randr::ModeInfo,
+    ) -> Result<Option<f64>, x11rb::errors::ReplyError> {
+        let mm_width: u32 = self.mm_width()?;
+        if mm_width == 0 {
+            return Ok(None);
+        }
+        Ok(Some(
+            f64::from(mode_info.width) * 25.4 / f64::from(mm_width),
+        ))
+    }
 
-        Ok(self)
+    // Position and size of monitor's current CRTC, if it has one.
+    pub(crate) fn geometry(
+        &self,
+    ) -> Result<Option<(i16, i16, u16, u16)>, x11rb::errors::ReplyError> {
+        let crtc: u32 = self.crtc()?;
+        if crtc == 0 {
+            return Ok(None);
+        }
+        let crtc_info: x11rb::protocol::randr::GetCrtcInfoReply =
+            self.connection.get_crtc_info(crtc)?;
+        Ok(Some((
+            crtc_info.x,
+            crtc_info.y,
+            crtc_info.width,
+            crtc_info.height,
+        )))
     }
 
     // Disable monitor.
-    pub(crate) fn disable(&self) -> Result<&Self, x11rb::errors::ReplyError> {
+    pub(crate) fn disable(&self) -> Result<&Self, crate::errors::SetCrtcConfigError> {
         // Print monitor info.
         self.print_monitor();
 
@@ -160,22 +348,97 @@ impl<'a> Monitor<'a> {
         }
 
         // Set CRTC config.
-        self.connection.set_crtc_config(
-            crtc,
-            0,
-            0,
-            0,
-            x11rb::protocol::randr::Rotation::ROTATE0,
-            &[],
-            crtc,
-        )?;
+        self.connection
+            .set_crtc_config(crtc, &crate::crtc_config::CrtcConfig::disabled(), crtc)?;
 
         Ok(self)
     }
 
     // Set output as primary.
-    pub(crate) fn set_primary(&self) -> Result<&Self, x11rb::errors::ConnectionError> {
+    pub(crate) fn set_primary(&self) -> Result<&Self, x11rb::errors::ReplyError> {
         self.connection.set_output_primary(self.output)?;
         Ok(self)
     }
 }
+
+// Is connection status connected, or unknown when those are included?
+fn is_connected(status: x11rb::protocol::randr::Connection, include_unknown: bool) -> bool {
+    match status {
+        x11rb::protocol::randr::Connection::CONNECTED => true,
+        x11rb::protocol::randr::Connection::UNKNOWN => include_unknown,
+        _ => false,
+    }
+}
+
+// Does text match glob pattern with * and ? wildcards?
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            glob_matches(&pattern[1..], text)
+                || (!text.is_empty() && glob_matches(pattern, &text[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob_matches(&pattern[1..], &text[1..]),
+        (Some(p), Some(t)) if p == t => glob_matches(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
+
+// Monitor information line for value, or for error getting it.
+fn info<T: std::fmt::Debug>(label: &str, value: Result<T, impl std::fmt::Display>) -> String {
+    match value {
+        Ok(value) => format!("{}: {:?}", label, value),
+        Err(error) => format!("{}: error {}", label, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn only_connected_outputs_are_used() {
+        assert!(super::is_connected(
+            x11rb::protocol::randr::Connection::CONNECTED,
+            false
+        ));
+        assert!(!super::is_connected(
+            x11rb::protocol::randr::Connection::DISCONNECTED,
+            false
+        ));
+        assert!(!super::is_connected(
+            x11rb::protocol::randr::Connection::UNKNOWN,
+            false
+        ));
+    }
+
+    #[test]
+    fn unknown_outputs_are_used_when_included() {
+        assert!(super::is_connected(
+            x11rb::protocol::randr::Connection::UNKNOWN,
+            true
+        ));
+        assert!(!super::is_connected(
+            x11rb::protocol::randr::Connection::DISCONNECTED,
+            true
+        ));
+    }
+
+    #[test]
+    fn glob_matches_literally() {
+        assert!(super::glob_matches(b"DP-1", b"DP-1"));
+        assert!(!super::glob_matches(b"DP-1", b"DP-10"));
+        assert!(!super::glob_matches(b"DP-1", b"DP-"));
+    }
+
+    #[test]
+    fn glob_matches_wildcards() {
+        assert!(super::glob_matches(b"DP-*", b"DP-1"));
+        assert!(super::glob_matches(b"DP-*", b"DP-"));
+        assert!(super::glob_matches(b"*-1", b"HDMI-1"));
+        assert!(super::glob_matches(b"D*-*1", b"DP-2-1"));
+        assert!(super::glob_matches(b"*", b""));
+        assert!(super::glob_matches(b"DP-?", b"DP-2"));
+        assert!(!super::glob_matches(b"DP-?", b"DP-"));
+        assert!(!super::glob_matches(b"DP-?", b"DP-10"));
+        assert!(!super::glob_matches(b"HDMI-*", b"DP-1"));
+    }
+}
@@ -0,0 +1,167 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// ID of the last notification, so that new one replaces it.
+#[cfg(feature = "dbus")]
+static LAST_ID: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+// Notify that monitor group was applied for connected monitors.
+pub(crate) fn applied(config: &crate::config::Config, label: &str, monitors: &[&str]) {
+    if config.notifications {
+        send(
+            &format!("Applied {}", label),
+            &format!("Monitors: {}", monitors.join(", ")),
+            false,
+        );
+    }
+}
+
+// Notify that selecting or applying monitor group failed.
+pub(crate) fn failed(config: &crate::config::Config, error: &crate::errors::X11EDIDAutoError) {
+    if config.notifications {
+        let (summary, body, critical): (&str, String, bool) = failure(error);
+        send(summary, &body, critical);
+    }
+}
+
+// Summary, body and criticality of notification for failure.
+// No match is normal, when monitors are not in config yet.
+fn failure(error: &crate::errors::X11EDIDAutoError) -> (&'static str, String, bool) {
+    match error {
+        crate::errors::X11EDIDAutoError::NoMonitorGroupWithAllMonitorsPresentError(error) => {
+            ("No matching monitor group", error.summary(), false)
+        }
+        error => ("Failed to apply monitor group", error.to_string(), true),
+    }
+}
+
+// Send notification, only printing errors.
+#[cfg(feature = "dbus")]
+fn send(summary: &str, body: &str, critical: bool) {
+    // Urgency is 1 for normal and 2 for critical.
+    let mut hints: std::collections::HashMap<&str, zbus::zvariant::Value> =
+        std::collections::HashMap::new();
+    hints.insert(
+        "urgency",
+        zbus::zvariant::Value::U8(if critical { 2 } else { 1 }),
+    );
+
+    let body: String = escape(body);
+    let result: Result<u32, zbus::Error> =
+        zbus::blocking::Connection::session().and_then(|connection| {
+            connection
+                .call_method(
+                    Some("org.freedesktop.Notifications"),
+                    "/org/freedesktop/Notifications",
+                    Some("org.freedesktop.Notifications"),
+                    "Notify",
+                    &(
+                        "x11_edid_auto",
+                        LAST_ID.load(std::sync::atomic::Ordering::Relaxed),
+                        "video-display",
+                        summary,
+                        body,
+                        Vec::<&str>::new(),
+                        hints,
+                        -1i32,
+                    ),
+                )?
+                .body()
+                .deserialize()
+        });
+    match result {
+        Ok(id) => LAST_ID.store(id, std::sync::atomic::Ordering::Relaxed),
+        Err(error) => crate::log::error(&error),
+    }
+}
+
+// Send notification with notify-send, when D-Bus support was not built in.
+#[cfg(not(feature = "dbus"))]
+fn send(summary: &str, body: &str, critical: bool) {
+    let result: Result<std::process::Child, std::io::Error> =
+        std::process::Command::new("notify-send")
+            .arg("--app-name=x11_edid_auto")
+            .arg("--icon=video-display")
+            .arg(if critical {
+                "--urgency=critical"
+            } else {
+                "--urgency=normal"
+            })
+            .arg("--")
+            .arg(summary)
+            .arg(escape(body))
+            .stdin(std::process::Stdio::null())
+            .spawn();
+    match result {
+        // Wait for it elsewhere, since it can take long without notification daemon.
+        Ok(mut child) => {
+            std::thread::spawn(move || match child.wait() {
+                Ok(status) if !status.success() => {
+                    crate::log::error(format!("notify-send failed with {}", status))
+                }
+                Ok(_) => {}
+                Err(error) => crate::log::error(&error),
+            });
+        }
+        Err(error) => crate::log::error(format!("Could not run notify-send: {}", error)),
+    }
+}
+
+// Body of notification can have markup, so escape it.
+fn escape(body: &str) -> String {
+    body.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn no_match_lists_unknown_monitors() {
+        let error: crate::errors::X11EDIDAutoError =
+            crate::errors::NoMonitorGroupWithAllMonitorsPresentError::new(
+                vec![vec!["name: DP-1".to_string()]],
+                vec![("DP_1".to_string(), "AAA:0001:00000001".to_string())],
+            )
+            .into();
+        assert_eq!(
+            super::failure(&error),
+            (
+                "No matching monitor group",
+                "Unknown monitors, add to [monitors] in config:\nDP_1 = \"AAA:0001:00000001\""
+                    .to_string(),
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn no_match_with_known_monitors_says_so() {
+        let error: crate::errors::X11EDIDAutoError =
+            crate::errors::NoMonitorGroupWithAllMonitorsPresentError::new(Vec::new(), Vec::new())
+                .into();
+        let (_, body, _): (&str, String, bool) = super::failure(&error);
+        assert_eq!(
+            body,
+            "All connected monitors are known, but no monitor group matches them."
+        );
+    }
+
+    #[test]
+    fn other_failures_are_critical() {
+        let error: crate::errors::X11EDIDAutoError =
+            crate::errors::NoMonitorsFoundError::new().into();
+        let (summary, body, critical): (&str, String, bool) = super::failure(&error);
+        assert_eq!(summary, "Failed to apply monitor group");
+        assert_eq!(body, error.to_string());
+        assert!(critical);
+    }
+
+    #[test]
+    fn markup_is_escaped() {
+        assert_eq!(
+            super::escape("<b>DP-1</b> & eDP-1"),
+            "&lt;b&gt;DP-1&lt;/b&gt; &amp; eDP-1"
+        );
+    }
+}
@@ -0,0 +1,871 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Structure for planned configuration of enabled CRTC.
+struct PlannedCrtc<'a, 'b> {
+    // Monitors driven by CRTC, more than one when they are mirrored on the same CRTC.
+    monitors: Vec<&'b crate::monitor::Monitor<'a>>,
+    // CRTC to use.
+    crtc: u32,
+    // Mode to use.
+    mode_info: x11rb::protocol::randr::ModeInfo,
+    // DPI, if physical size is known.
+    dpi: Option<f64>,
+    // Scale for CRTC transform.
+    scale_x: f64,
+    scale_y: f64,
+    // Position.
+    x: i16,
+    y: i16,
+    // Index of planned CRTC that this one mirrors.
+    mirror_of: Option<usize>,
+    // Scale to fit the content of the CRTC that this one mirrors.
+    scale_to_fit: bool,
+}
+
+// Methods for planned CRTC.
+impl<'a, 'b> PlannedCrtc<'a, 'b> {
+    // Create a new planned CRTC for monitors with mode.
+    fn new(
+        monitors: Vec<&'b crate::monitor::Monitor<'a>>,
+        mode_info: x11rb::protocol::randr::ModeInfo,
+        mirror_of: Option<usize>,
+    ) -> Result<Self, x11rb::errors::ReplyError> {
+        Ok(PlannedCrtc {
+            dpi: monitors[0].dpi(&mode_info)?,
+            monitors,
+            crtc: 0,
+            mode_info,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            x: 0,
+            y: 0,
+            mirror_of,
+            scale_to_fit: false,
+        })
+    }
+
+    // Width after scaling.
+    fn width(&self) -> u16 {
+        crate::crtc_config::CrtcConfig::scaled_size(self.mode_info.width, self.scale_x)
+    }
+
+    // Height after scaling.
+    fn height(&self) -> u16 {
+        crate::crtc_config::CrtcConfig::scaled_size(self.mode_info.height, self.scale_y)
+    }
+
+    // Outputs for monitors.
+    fn outputs(&self) -> Vec<u32> {
+        self.monitors
+            .iter()
+            .map(|monitor| monitor.output())
+            .collect()
+    }
+}
+
+// Structure for plan of monitor group.
+pub(crate) struct Plan<'a, 'b> {
+    // Connection to X server.
+    connection: &'a crate::connection::Connection,
+    // CRTCs to enable, first one has primary monitor.
+    enabled: Vec<PlannedCrtc<'a, 'b>>,
+    // Monitors to disable.
+    disabled: Vec<&'b crate::monitor::Monitor<'a>>,
+    // Monitors to keep as they are.
+    kept: Vec<&'b crate::monitor::Monitor<'a>>,
+    // Screen size in pixels.
+    screen_width: u16,
+    screen_height: u16,
+}
+
+// Methods for plan.
+impl<'a, 'b> Plan<'a, 'b> {
+    // Create a new plan for monitor group.
+    pub(crate) fn new(
+        connection: &'a crate::connection::Connection,
+        monitor_group: &crate::config::MonitorGroup,
+        unlisted: crate::config::Unlisted,
+        monitor_map: &'b std::collections::HashMap<String, crate::monitor::Monitor<'a>>,
+        disconnected: &'b [crate::monitor::Monitor<'a>],
+    ) -> Result<Self, crate::errors::PlanNewError> {
+        // Get monitors in group, optional ones only when they are present.
+        let mut monitors: Vec<(&String, &'b crate::monitor::Monitor<'a>)> = Vec::new();
+        for monitor_id in &monitor_group.monitors {
+            match monitor_map.get(monitor_id) {
+                Some(monitor) => monitors.push((monitor_id, monitor)),
+                None if monitor_group.optional.contains(monitor_id) => {}
+                None => {
+                    return Err(crate::errors::PlanNewError::MonitorNotFoundError(
+                        crate::errors::MonitorNotFoundError::new(monitor_id.to_string()),
+                    ))
+                }
+            };
+        }
+
+        // Plan CRTCs for monitors, mirrored ones are planned together at first of them.
+        let mut enabled: Vec<PlannedCrtc<'a, 'b>> = Vec::new();
+        let mut mirror_planned: bool = false;
+        for (monitor_id, monitor) in &monitors {
+            if !monitor_group.mirror.contains(monitor_id) {
+                let mode_info: x11rb::protocol::randr::ModeInfo = monitor.mode_info()?;
+                enabled.push(PlannedCrtc::new(vec![*monitor], mode_info, None)?);
+            } else if !mirror_planned {
+                let mirrored: Vec<&'b crate::monitor::Monitor<'a>> = monitors
+                    .iter()
+                    .filter(|(monitor_id, _)| monitor_group.mirror.contains(monitor_id))
+                    .map(|(_, monitor)| *monitor)
+                    .collect();
+                let index: usize = enabled.len();
+                enabled.extend(Self::plan_mirror(
+                    &mirrored,
+                    index,
+                    monitor_group.mirror_scale,
+                )?);
+                mirror_planned = true;
+            }
+        }
+
+        // Monitors that are not in monitor group, in stable order.
+        let mut unlisted_monitors: Vec<&'b crate::monitor::Monitor<'a>> = monitor_map
+            .iter()
+            .filter(|(monitor_id, _)| !monitor_group.monitors.contains(monitor_id))
+            .map(|(_, monitor)| monitor)
+            .collect();
+        unlisted_monitors.sort_by_key(|monitor| monitor.monitor_id());
+
+        // Handle them with given policy.
+        let mut disabled: Vec<&'b crate::monitor::Monitor<'a>> = Vec::new();
+        let mut kept: Vec<&'b crate::monitor::Monitor<'a>> = Vec::new();
+        let mut mirror_primary: Vec<&'b crate::monitor::Monitor<'a>> = Vec::new();
+        match unlisted {
+            crate::config::Unlisted::Disable => disabled = unlisted_monitors,
+            crate::config::Unlisted::Keep => kept = unlisted_monitors,
+            crate::config::Unlisted::ExtendRight => {
+                for monitor in unlisted_monitors {
+                    enabled.push(PlannedCrtc::new(vec![monitor], monitor.mode_info()?, None)?);
+                }
+            }
+            crate::config::Unlisted::MirrorPrimary => mirror_primary = unlisted_monitors,
+        }
+
+        // Disconnected monitors that still have CRTC are always disabled.
+        disabled.extend(disconnected);
+
+        // Scale monitors to target DPI.
+        if let Some(auto_scale) = monitor_group.auto_scale {
+            match Self::target_dpi(auto_scale, &enabled) {
+                Some(target_dpi) => {
+                    for planned_crtc in enabled.iter_mut() {
+                        if let (None, Some(dpi)) = (planned_crtc.mirror_of, planned_crtc.dpi) {
+                            planned_crtc.scale_x = target_dpi / dpi;
+                            planned_crtc.scale_y = target_dpi / dpi;
+                        }
+                    }
+                }
+                None => eprintln!("Could not determine target DPI, not scaling monitors!"),
+            }
+        }
+
+        // Mirror the primary monitor as it is after scaling.
+        for monitor in mirror_primary {
+            enabled.push(match enabled.first() {
+                Some(primary) => Self::plan_mirror_primary(monitor, primary)?,
+                None => PlannedCrtc::new(vec![monitor], monitor.mode_info()?, None)?,
+            });
+        }
+
+        // Assign CRTCs for monitors.
+        Self::assign_crtcs(connection, monitor_map, disconnected, &kept, &mut enabled)?;
+
+        // Scale mirrors to match the content they mirror.
+        Self::scale_mirrors(&mut enabled);
+
+        // Place monitors from left to right.
+        let screen_size_range: x11rb::protocol::randr::GetScreenSizeRangeReply =
+            connection.screen_size_range()?;
+        Self::place(
+            &mut enabled,
+            screen_size_range.max_width,
+            screen_size_range.max_height,
+        )?;
+
+        // Screen size is bounding box of enabled and kept monitors,
+        let (width, height): (u32, u32) = Self::bounding_box(&enabled, &kept)?;
+        // which has to fit in the range that X server supports.
+        if width > u32::from(screen_size_range.max_width)
+            || height > u32::from(screen_size_range.max_height)
+        {
+            return Err(crate::errors::PlanNewError::ScreenSizeTooLargeError(
+                crate::errors::ScreenSizeTooLargeError::new(
+                    width,
+                    height,
+                    screen_size_range.max_width,
+                    screen_size_range.max_height,
+                ),
+            ));
+        }
+
+        Ok(Plan {
+            connection,
+            enabled,
+            disabled,
+            kept,
+            screen_width: std::cmp::max(width as u16, screen_size_range.min_width),
+            screen_height: std::cmp::max(height as u16, screen_size_range.min_height),
+        })
+    }
+
+    // Plan CRTCs for mirrored monitors, first of them is at given index.
+    fn plan_mirror(
+        monitors: &[&'b crate::monitor::Monitor<'a>],
+        index: usize,
+        scale_to_fit: bool,
+    ) -> Result<Vec<PlannedCrtc<'a, 'b>>, crate::errors::PlanNewError> {
+        // Get modes for all of the monitors.
+        let mut modes: Vec<Vec<x11rb::protocol::randr::ModeInfo>> = Vec::new();
+        for monitor in monitors {
+            modes.push(monitor.modes()?);
+        }
+
+        // If all of the monitors can share a CRTC
+        if Self::can_share_crtc(monitors)? {
+            // and have common mode,
+            if let Some(mode_info) =
+                crate::monitor::Monitor::best_mode(modes[0].iter().filter(|mode_info| {
+                    modes
+                        .iter()
+                        .all(|other| other.iter().any(|other| other.id == mode_info.id))
+                }))
+            {
+                // drive them all from one CRTC.
+                println!("Mirroring monitors on one CRTC.");
+                return Ok(vec![PlannedCrtc::new(monitors.to_vec(), mode_info, None)?]);
+            }
+        }
+
+        // Find the largest resolution that is common for all of the monitors.
+        let common_resolution: Option<(u16, u16)> =
+            crate::monitor::Monitor::best_mode(modes[0].iter().filter(|mode_info| {
+                modes.iter().all(|other| {
+                    other.iter().any(|other| {
+                        other.width == mode_info.width && other.height == mode_info.height
+                    })
+                })
+            }))
+            .map(|mode_info| (mode_info.width, mode_info.height));
+
+        // Plan separate CRTCs on top of each other.
+        let mut planned_crtcs: Vec<PlannedCrtc<'a, 'b>> = Vec::new();
+        for (monitor_index, (monitor, monitor_modes)) in monitors.iter().zip(&modes).enumerate() {
+            // Use common resolution or monitor's best mode.
+            let mode_info: x11rb::protocol::randr::ModeInfo = match common_resolution {
+                Some((width, height)) => crate::monitor::Monitor::best_mode(
+                    monitor_modes
+                        .iter()
+                        .filter(|mode_info| mode_info.width == width && mode_info.height == height),
+                ),
+                None => crate::monitor::Monitor::best_mode(monitor_modes.iter()),
+            }
+            .ok_or_else(crate::errors::NoModesError::new)
+            .map_err(crate::errors::MonitorModeInfoError::from)?;
+            let mut planned_crtc: PlannedCrtc<'a, 'b> = PlannedCrtc::new(
+                vec![*monitor],
+                mode_info,
+                if monitor_index == 0 {
+                    None
+                } else {
+                    Some(index)
+                },
+            )?;
+            planned_crtc.scale_to_fit = scale_to_fit;
+            planned_crtcs.push(planned_crtc);
+        }
+        Ok(planned_crtcs)
+    }
+
+    // Plan CRTC for monitor that mirrors the primary one.
+    fn plan_mirror_primary(
+        monitor: &'b crate::monitor::Monitor<'a>,
+        primary: &PlannedCrtc<'a, 'b>,
+    ) -> Result<PlannedCrtc<'a, 'b>, crate::errors::PlanNewError> {
+        // Use the same resolution as primary has after scaling,
+        let modes: Vec<x11rb::protocol::randr::ModeInfo> = monitor.modes()?;
+        if let Some(mode_info) = Self::mode_of_size(&modes, primary.width(), primary.height()) {
+            return Ok(PlannedCrtc::new(vec![monitor], mode_info, Some(0))?);
+        }
+
+        // or scale best mode to fit.
+        let mut planned_crtc: PlannedCrtc<'a, 'b> =
+            PlannedCrtc::new(vec![monitor], monitor.mode_info()?, Some(0))?;
+        planned_crtc.scale_to_fit = true;
+        Ok(planned_crtc)
+    }
+
+    // Best mode of given size.
+    fn mode_of_size(
+        modes: &[x11rb::protocol::randr::ModeInfo],
+        width: u16,
+        height: u16,
+    ) -> Option<x11rb::protocol::randr::ModeInfo> {
+        crate::monitor::Monitor::best_mode(
+            modes
+                .iter()
+                .filter(|mode_info| mode_info.width == width && mode_info.height == height),
+        )
+    }
+
+    // Scale mirrors to fit the content they mirror, or the same as it is
+    // scaled when their modes have the same size.
+    fn scale_mirrors(enabled: &mut [PlannedCrtc<'a, 'b>]) {
+        for index in 0..enabled.len() {
+            if let Some(mirror_of) = enabled[index].mirror_of {
+                let mirrored: &PlannedCrtc = &enabled[mirror_of];
+                let (width, height): (u16, u16) = (mirrored.width(), mirrored.height());
+                let (mode_width, mode_height): (u16, u16) =
+                    (mirrored.mode_info.width, mirrored.mode_info.height);
+                let (scale_x, scale_y): (f64, f64) = (mirrored.scale_x, mirrored.scale_y);
+                let planned_crtc: &mut PlannedCrtc = &mut enabled[index];
+                if planned_crtc.scale_to_fit {
+                    planned_crtc.scale_x =
+                        f64::from(width) / f64::from(planned_crtc.mode_info.width);
+                    planned_crtc.scale_y =
+                        f64::from(height) / f64::from(planned_crtc.mode_info.height);
+                } else if planned_crtc.mode_info.width == mode_width
+                    && planned_crtc.mode_info.height == mode_height
+                {
+                    planned_crtc.scale_x = scale_x;
+                    planned_crtc.scale_y = scale_y;
+                }
+            }
+        }
+    }
+
+    // Can all of the monitors share a CRTC?
+    fn can_share_crtc(
+        monitors: &[&'b crate::monitor::Monitor<'a>],
+    ) -> Result<bool, x11rb::errors::ReplyError> {
+        for monitor in monitors {
+            // All of the other monitors have to be its clones.
+            let clones: Vec<u32> = monitor.clones()?;
+            if monitors.iter().any(|other| {
+                other.output() != monitor.output() && !clones.contains(&other.output())
+            }) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // Assign CRTCs for enabled CRTCs from possible CRTCs of their monitors.
+    fn assign_crtcs(
+        connection: &crate::connection::Connection,
+        monitor_map: &std::collections::HashMap<String, crate::monitor::Monitor<'a>>,
+        disconnected: &[crate::monitor::Monitor<'a>],
+        kept: &[&'b crate::monitor::Monitor<'a>],
+        enabled: &mut [PlannedCrtc<'a, 'b>],
+    ) -> Result<(), crate::errors::PlanNewError> {
+        // Outputs that this plan controls, disconnected ones are disabled before assigning.
+        let kept_outputs: Vec<u32> = kept.iter().map(|monitor| monitor.output()).collect();
+        let outputs: Vec<u32> = monitor_map
+            .values()
+            .chain(disconnected)
+            .map(|monitor| monitor.output())
+            .filter(|output| !kept_outputs.contains(output))
+            .collect();
+
+        // Find CRTCs that are free and ones that are used by other outputs.
+        let mut crtcs_free: Vec<u32> = Vec::new();
+        let mut crtcs_reserved: Vec<u32> = Vec::new();
+        for crtc in connection.crtcs()? {
+            let crtc_info: x11rb::protocol::randr::GetCrtcInfoReply =
+                connection.get_crtc_info(crtc)?;
+            if crtc_info.mode == 0 {
+                crtcs_free.push(crtc);
+            } else if crtc_info
+                .outputs
+                .iter()
+                .any(|output| !outputs.contains(output))
+            {
+                crtcs_reserved.push(crtc);
+            }
+        }
+
+        // Candidates in order of preference: current CRTC, free CRTCs and then the rest.
+        let mut candidates: Vec<Vec<u32>> = Vec::new();
+        for planned_crtc in enabled.iter() {
+            let crtc_current: u32 = planned_crtc.monitors[0].crtc()?;
+            let mut crtcs: Vec<u32> = planned_crtc.monitors[0]
+                .possible_crtcs()?
+                .into_iter()
+                .filter(|crtc| !crtcs_reserved.contains(crtc))
+                .collect();
+            // CRTC has to be possible for all of its monitors.
+            for monitor in &planned_crtc.monitors[1..] {
+                let possible_crtcs: Vec<u32> = monitor.possible_crtcs()?;
+                crtcs.retain(|crtc| possible_crtcs.contains(crtc));
+            }
+            crtcs.sort_by_key(|crtc| (*crtc != crtc_current, !crtcs_free.contains(crtc)));
+            candidates.push(crtcs);
+        }
+
+        // Assign CRTCs.
+        match crate::crtc_assignment::assign(&candidates) {
+            Some(crtcs) => {
+                for (planned_crtc, crtc) in enabled.iter_mut().zip(crtcs) {
+                    planned_crtc.crtc = crtc;
+                }
+                Ok(())
+            }
+            None => Err(crate::errors::PlanNewError::NoCrtcAssignmentError(
+                crate::errors::NoCrtcAssignmentError::new(
+                    enabled
+                        .iter()
+                        .flat_map(|planned_crtc| &planned_crtc.monitors)
+                        .map(|monitor| monitor.key())
+                        .collect(),
+                ),
+            )),
+        }
+    }
+
+    // Target DPI for automatic scaling.
+    fn target_dpi(
+        auto_scale: crate::config::AutoScale,
+        enabled: &[PlannedCrtc<'a, 'b>],
+    ) -> Option<f64> {
+        match auto_scale {
+            crate::config::AutoScale::Mode(crate::config::AutoScaleMode::MatchLowest) => enabled
+                .iter()
+                .filter(|planned_crtc| planned_crtc.mirror_of.is_none())
+                .filter_map(|planned_crtc| planned_crtc.dpi)
+                .min_by(f64::total_cmp),
+            crate::config::AutoScale::Mode(crate::config::AutoScaleMode::MatchPrimary) => {
+                enabled.first().and_then(|planned_crtc| planned_crtc.dpi)
+            }
+            crate::config::AutoScale::Dpi(dpi) => Some(dpi),
+        }
+        .filter(|dpi| *dpi > 0.0)
+    }
+
+    // DPI of the primary monitor after scaling.
+    pub(crate) fn dpi(&self) -> Option<f64> {
+        self.enabled
+            .first()
+            .and_then(|planned_crtc| planned_crtc.dpi.map(|dpi| dpi * planned_crtc.scale_x))
+    }
+
+    // Place monitors from left to right and mirrors on top of what they mirror.
+    // Fails when positions do not fit, before screen size could be checked.
+    fn place(
+        enabled: &mut [PlannedCrtc<'a, 'b>],
+        max_width: u16,
+        max_height: u16,
+    ) -> Result<(), crate::errors::ScreenSizeTooLargeError> {
+        let mut x: i16 = 0;
+        for index in 0..enabled.len() {
+            if let Some(mirror_of) = enabled[index].mirror_of {
+                enabled[index].x = enabled[mirror_of].x;
+                enabled[index].y = enabled[mirror_of].y;
+                continue;
+            }
+            enabled[index].x = x;
+            let width: u16 = enabled[index].width();
+            x = match i16::try_from(width)
+                .ok()
+                .and_then(|width| x.checked_add(width))
+            {
+                Some(x) => x,
+                None => {
+                    return Err(crate::errors::ScreenSizeTooLargeError::new(
+                        (i32::from(x) + i32::from(width)) as u32,
+                        enabled
+                            .iter()
+                            .map(|planned_crtc| u32::from(planned_crtc.height()))
+                            .max()
+                            .unwrap_or_default(),
+                        max_width,
+                        max_height,
+                    ))
+                }
+            };
+        }
+        Ok(())
+    }
+
+    // Bounding box of CRTCs and kept monitors in pixels.
+    fn bounding_box(
+        enabled: &[PlannedCrtc<'a, 'b>],
+        kept: &[&'b crate::monitor::Monitor<'a>],
+    ) -> Result<(u32, u32), x11rb::errors::ReplyError> {
+        // Rectangles of CRTCs and kept monitors.
+        let mut rectangles: Vec<(i16, i16, u16, u16)> = enabled
+            .iter()
+            .map(|planned_crtc| {
+                (
+                    planned_crtc.x,
+                    planned_crtc.y,
+                    planned_crtc.width(),
+                    planned_crtc.height(),
+                )
+            })
+            .collect();
+        for monitor in kept {
+            if let Some(geometry) = monitor.geometry()? {
+                rectangles.push(geometry);
+            }
+        }
+
+        Ok(rectangles.iter().fold(
+            (0, 0),
+            |(width, height), (x, y, rectangle_width, rectangle_height)| {
+                (
+                    std::cmp::max(
+                        width,
+                        (i32::from(*x) + i32::from(*rectangle_width)).max(0) as u32,
+                    ),
+                    std::cmp::max(
+                        height,
+                        (i32::from(*y) + i32::from(*rectangle_height)).max(0) as u32,
+                    ),
+                )
+            },
+        ))
+    }
+
+    // Print plan.
+    pub(crate) fn print(&self) -> &Self {
+        println!("Plan:");
+        for planned_crtc in &self.enabled {
+            for monitor in &planned_crtc.monitors {
+                println!("Monitor:");
+                for info in monitor.monitor_info() {
+                    println!("\t {}", info);
+                }
+            }
+            println!("\t new crtc: {:?}", planned_crtc.crtc);
+            println!(
+                "\t mode: {}x{}",
+                planned_crtc.mode_info.width, planned_crtc.mode_info.height
+            );
+            match planned_crtc.dpi {
+                Some(dpi) => println!("\t dpi: {:.1}", dpi),
+                None => println!("\t dpi: unknown"),
+            }
+            println!(
+                "\t scale: {:.3}x{:.3}",
+                planned_crtc.scale_x, planned_crtc.scale_y
+            );
+            println!("\t position: {}x{}", planned_crtc.x, planned_crtc.y);
+            println!(
+                "\t size: {}x{}",
+                planned_crtc.width(),
+                planned_crtc.height()
+            );
+            if let Some(mirror_of) = planned_crtc.mirror_of {
+                println!("\t mirrors: {}", self.enabled[mirror_of].monitors[0].key());
+            }
+        }
+        for monitor in &self.disabled {
+            println!("Disable monitor:");
+            for info in monitor.monitor_info() {
+                println!("\t {}", info);
+            }
+        }
+        for monitor in &self.kept {
+            println!("Keep monitor:");
+            for info in monitor.monitor_info() {
+                println!("\t {}", info);
+            }
+        }
+        println!("Screen size: {}x{}", self.screen_width, self.screen_height);
+        self
+    }
+
+    // Environment for hooks with primary output, geometry of outputs and screen size.
+    pub(crate) fn environment(
+        &self,
+    ) -> Result<Vec<(String, String)>, crate::errors::MonitorNameError> {
+        let mut geometries: Vec<String> = Vec::new();
+        for planned_crtc in &self.enabled {
+            for monitor in &planned_crtc.monitors {
+                geometries.push(format!(
+                    "{}={}x{}+{}+{}",
+                    monitor.name()?,
+                    planned_crtc.width(),
+                    planned_crtc.height(),
+                    planned_crtc.x,
+                    planned_crtc.y
+                ));
+            }
+        }
+        let mut environment: Vec<(String, String)> = vec![
+            ("X11_EDID_AUTO_GEOMETRY".to_string(), geometries.join(" ")),
+            (
+                "X11_EDID_AUTO_SCREEN".to_string(),
+                format!("{}x{}", self.screen_width, self.screen_height),
+            ),
+        ];
+        if let Some(primary) = self.enabled.first() {
+            environment.push((
+                "X11_EDID_AUTO_PRIMARY".to_string(),
+                primary.monitors[0].name()?,
+            ));
+        }
+        Ok(environment)
+    }
+
+    // Apply plan atomically.
+    pub(crate) fn apply(&self, dpi: Option<f64>) -> Result<&Self, crate::errors::PlanApplyError> {
+        // Grab server, so that nothing else changes it meanwhile.
+        self.connection.grab_server()?;
+
+        // Apply plan.
+        let result: Result<&Self, crate::errors::PlanApplyError> = self.apply_with_rollback(dpi);
+
+        // Always ungrab server, even if applying failed.
+        let ungrab_result: Result<&crate::connection::Connection, x11rb::errors::ReplyError> =
+            self.connection.ungrab_server();
+        result?;
+        ungrab_result?;
+        Ok(self)
+    }
+
+    // Apply plan, restoring previous configuration if it fails.
+    fn apply_with_rollback(
+        &self,
+        dpi: Option<f64>,
+    ) -> Result<&Self, crate::errors::PlanApplyError> {
+        // Take snapshot of current configuration.
+        let snapshot: crate::snapshot::Snapshot = crate::snapshot::Snapshot::new(self.connection)?;
+
+        // If applying fails
+        if let Err(plan_apply_steps_error) = self.apply_steps(dpi) {
+            // restore snapshot and return error.
+            return Err(crate::errors::PlanApplyError::RollbackError(
+                crate::errors::RollbackError::new(plan_apply_steps_error, snapshot.restore().err()),
+            ));
+        }
+
+        Ok(self)
+    }
+
+    // Apply steps of plan.
+    fn apply_steps(&self, dpi: Option<f64>) -> Result<&Self, crate::errors::PlanApplyStepsError> {
+        // Disable monitors that are not in monitor group.
+        println!("Disabling unused monitors...");
+        for monitor in &self.disabled {
+            monitor.disable()?;
+        }
+
+        // Disable monitors that move to another CRTC, so that their CRTCs are free.
+        for planned_crtc in &self.enabled {
+            for monitor in &planned_crtc.monitors {
+                let crtc_current: u32 = monitor.crtc()?;
+                if crtc_current != 0 && crtc_current != planned_crtc.crtc {
+                    println!("Disabling monitor that moves to another CRTC...");
+                    monitor.disable()?;
+                }
+            }
+        }
+
+        // Disable CRTCs that would fall outside of the new screen size,
+        // also ones on outputs that are not in monitor map, but not ones of kept monitors.
+        let mut kept_crtcs: Vec<u32> = Vec::new();
+        for monitor in &self.kept {
+            kept_crtcs.push(monitor.crtc()?);
+        }
+        for crtc in self.connection.crtcs()? {
+            if kept_crtcs.contains(&crtc) {
+                continue;
+            }
+            let crtc_info: x11rb::protocol::randr::GetCrtcInfoReply =
+                self.connection.get_crtc_info(crtc)?;
+            if crtc_info.mode != 0
+                && (i32::from(crtc_info.x) + i32::from(crtc_info.width)
+                    > i32::from(self.screen_width)
+                    || i32::from(crtc_info.y) + i32::from(crtc_info.height)
+                        > i32::from(self.screen_height))
+            {
+                println!(
+                    "Disabling CRTC {} that does not fit in the new screen size...",
+                    crtc
+                );
+                self.connection.set_crtc_config(
+                    crtc,
+                    &crate::crtc_config::CrtcConfig::disabled(),
+                    crtc,
+                )?;
+            }
+        }
+
+        // Grow screen, so that monitors fit in it while they are being set.
+        let (current_width, current_height): (u16, u16) = self.connection.screen_size()?;
+        let grown_width: u16 = std::cmp::max(current_width, self.screen_width);
+        let grown_height: u16 = std::cmp::max(current_height, self.screen_height);
+        if grown_width != current_width || grown_height != current_height {
+            self.connection
+                .set_screen_size(grown_width, grown_height, dpi)?;
+        }
+
+        // Enable CRTCs in monitor group.
+        for planned_crtc in &self.enabled {
+            for monitor in &planned_crtc.monitors {
+                monitor.print_monitor();
+            }
+            self.connection.set_crtc_config(
+                planned_crtc.crtc,
+                &crate::crtc_config::CrtcConfig::new(
+                    planned_crtc.x,
+                    planned_crtc.y,
+                    planned_crtc.mode_info.id,
+                    x11rb::protocol::randr::Rotation::ROTATE0,
+                    planned_crtc.scale_x,
+                    planned_crtc.scale_y,
+                    planned_crtc.outputs(),
+                ),
+                planned_crtc.monitors[0].crtc()?,
+            )?;
+        }
+
+        // Set primary monitor.
+        if let Some(planned_crtc) = self.enabled.first() {
+            planned_crtc.monitors[0].set_primary()?;
+        }
+
+        // Shrink screen to the new size.
+        self.connection
+            .set_screen_size(self.screen_width, self.screen_height, dpi)?;
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Planned CRTC with mode size and scale, mirroring another one if given.
+    fn planned_crtc<'a, 'b>(
+        (width, height): (u16, u16),
+        scale: f64,
+        mirror_of: Option<usize>,
+    ) -> super::PlannedCrtc<'a, 'b> {
+        super::PlannedCrtc {
+            monitors: Vec::new(),
+            crtc: 0,
+            mode_info: x11rb::protocol::randr::ModeInfo {
+                width,
+                height,
+                ..Default::default()
+            },
+            dpi: None,
+            scale_x: scale,
+            scale_y: scale,
+            x: 0,
+            y: 0,
+            mirror_of,
+            scale_to_fit: false,
+        }
+    }
+
+    #[test]
+    fn places_monitors_from_left_to_right() {
+        let mut enabled: Vec<super::PlannedCrtc> = vec![
+            planned_crtc((2560, 1440), 1.0, None),
+            planned_crtc((1920, 1080), 1.5, None),
+            planned_crtc((1920, 1080), 1.0, Some(0)),
+            planned_crtc((1280, 1024), 1.0, None),
+        ];
+        super::Plan::place(&mut enabled, 16384, 16384).unwrap();
+        let positions: Vec<(i16, i16)> = enabled
+            .iter()
+            .map(|planned_crtc| (planned_crtc.x, planned_crtc.y))
+            .collect();
+        assert_eq!(positions, vec![(0, 0), (2560, 0), (0, 0), (5440, 0)]);
+    }
+
+    #[test]
+    fn placing_too_wide_layout_fails() {
+        let mut enabled: Vec<super::PlannedCrtc> = vec![
+            planned_crtc((7680, 4320), 2.0, None),
+            planned_crtc((7680, 4320), 2.0, None),
+            planned_crtc((3840, 2160), 1.0, None),
+        ];
+        let error: crate::errors::ScreenSizeTooLargeError =
+            super::Plan::place(&mut enabled, 16384, 16384).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Screen size 34560x8640 exceeds maximum framebuffer size 16384x16384!"
+        );
+    }
+
+    #[test]
+    fn mirrors_are_scaled_like_what_they_mirror() {
+        let mut enabled: Vec<super::PlannedCrtc> = vec![
+            planned_crtc((2560, 1440), 1.5, None),
+            planned_crtc((2560, 1440), 1.0, Some(0)),
+            planned_crtc((1920, 1080), 1.0, Some(0)),
+            planned_crtc((1920, 1080), 1.0, Some(0)),
+        ];
+        enabled[3].scale_to_fit = true;
+        super::Plan::scale_mirrors(&mut enabled);
+        let sizes: Vec<(u16, u16)> = enabled
+            .iter()
+            .map(|planned_crtc| (planned_crtc.width(), planned_crtc.height()))
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![(3840, 2160), (3840, 2160), (1920, 1080), (3840, 2160)]
+        );
+    }
+
+    #[test]
+    fn mirror_mode_has_scaled_size_of_primary() {
+        let primary: super::PlannedCrtc = planned_crtc((2560, 1440), 1.5, None);
+        let modes: Vec<x11rb::protocol::randr::ModeInfo> = [(2560, 1440), (3840, 2160)]
+            .into_iter()
+            .map(|(width, height)| x11rb::protocol::randr::ModeInfo {
+                width,
+                height,
+                ..Default::default()
+            })
+            .collect();
+        let mode_info: x11rb::protocol::randr::ModeInfo =
+            super::Plan::mode_of_size(&modes, primary.width(), primary.height()).unwrap();
+        assert_eq!((mode_info.width, mode_info.height), (3840, 2160));
+    }
+
+    #[test]
+    fn bounding_box_of_nothing_is_empty() {
+        assert_eq!(super::Plan::bounding_box(&[], &[]).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn bounding_box_covers_all_crtcs() {
+        let mut enabled: Vec<super::PlannedCrtc> = vec![
+            planned_crtc((1920, 1080), 1.0, None),
+            planned_crtc((1080, 1920), 1.0, None),
+        ];
+        enabled[1].x = 1920;
+        assert_eq!(
+            super::Plan::bounding_box(&enabled, &[]).unwrap(),
+            (3000, 1920)
+        );
+    }
+
+    #[test]
+    fn bounding_box_uses_scaled_size() {
+        let enabled: Vec<super::PlannedCrtc> = vec![planned_crtc((1920, 1080), 1.5, None)];
+        assert_eq!(
+            super::Plan::bounding_box(&enabled, &[]).unwrap(),
+            (2880, 1620)
+        );
+    }
+
+    #[test]
+    fn bounding_box_ignores_negative_extents() {
+        let mut enabled: Vec<super::PlannedCrtc> = vec![planned_crtc((100, 100), 1.0, None)];
+        enabled[0].x = -200;
+        enabled[0].y = -200;
+        assert_eq!(super::Plan::bounding_box(&enabled, &[]).unwrap(), (0, 0));
+    }
+}
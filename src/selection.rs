@@ -0,0 +1,307 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Does monitor group match connected monitors?
+fn matches(monitor_group: &crate::config::MonitorGroup, connected: &[&str]) -> bool {
+    let is_connected = |monitor_id: &str| connected.contains(&monitor_id);
+    // All of the group's required monitors have to be connected,
+    required_present(monitor_group, is_connected)
+        // at least one of its monitors has to be connected,
+        && monitor_group
+            .monitors
+            .iter()
+            .any(|monitor_id| is_connected(monitor_id))
+        // and for exact group no other monitors can be connected.
+        && (!monitor_group.exact
+            || connected
+                .iter()
+                .all(|monitor_id| monitor_group.monitors.iter().any(|other| other == monitor_id)))
+}
+
+// Are all of the group's required monitors connected?
+pub(crate) fn required_present(
+    monitor_group: &crate::config::MonitorGroup,
+    is_connected: impl Fn(&str) -> bool,
+) -> bool {
+    monitor_group
+        .required()
+        .all(|monitor_id| is_connected(monitor_id))
+}
+
+// All monitor groups matching connected monitors with their indexes, most preferred first.
+pub(crate) fn matching<'c>(
+    config: &'c crate::config::Config,
+    monitor_map: &std::collections::HashMap<String, crate::monitor::Monitor>,
+) -> Vec<(usize, &'c crate::config::MonitorGroup)> {
+    // Loop monitor groups.
+    println!("Monitor groups...");
+    let connected: Vec<&str> = monitor_map.keys().map(String::as_str).collect();
+    let mut matching: Vec<(usize, &crate::config::MonitorGroup)> = Vec::new();
+    for (index, monitor_group) in config.monitor_groups().enumerate() {
+        if matches(monitor_group, &connected) {
+            println!(
+                "{} had all of it's required monitors {:?} present!",
+                monitor_group.label(index),
+                monitor_group.monitors
+            );
+            matching.push((index, monitor_group));
+        } else if monitor_group.exact {
+            eprintln!(
+                "{} did not have exactly it's monitors {:?} present!",
+                monitor_group.label(index),
+                monitor_group.monitors
+            );
+        } else {
+            eprintln!(
+                "{} did not have all of it's monitors {:?} present!",
+                monitor_group.label(index),
+                monitor_group.monitors
+            );
+        }
+    }
+
+    // Order by score with monitors that are connected.
+    sort(config, &mut matching, |monitor_id| {
+        monitor_map.contains_key(monitor_id)
+    });
+
+    matching
+}
+
+// Order monitor groups by preference, when selecting by score:
+// covered monitors, then priority, then file order.
+fn sort(
+    config: &crate::config::Config,
+    monitor_groups: &mut [(usize, &crate::config::MonitorGroup)],
+    is_connected: impl Fn(&str) -> bool,
+) {
+    if let crate::config::Selection::Score = config.selection {
+        let covered = |monitor_group: &crate::config::MonitorGroup| -> usize {
+            monitor_group
+                .monitors
+                .iter()
+                .filter(|monitor_id| is_connected(monitor_id))
+                .count()
+        };
+        monitor_groups.sort_by(|(index_a, group_a), (index_b, group_b)| {
+            covered(group_b)
+                .cmp(&covered(group_a))
+                .then_with(|| group_b.priority.cmp(&group_a.priority))
+                .then_with(|| index_a.cmp(index_b))
+        });
+    }
+}
+
+// Index of the most preferred monitor group, if all of the monitors were connected.
+pub(crate) fn preferred(config: &crate::config::Config) -> Option<usize> {
+    let mut monitor_groups: Vec<(usize, &crate::config::MonitorGroup)> =
+        config.monitor_groups().enumerate().collect();
+    sort(config, &mut monitor_groups, |_| true);
+    monitor_groups.first().map(|(index, _)| *index)
+}
+
+// Profile with given name, when all of its required monitors are connected.
+pub(crate) fn profile<'c>(
+    config: &'c crate::config::Config,
+    name: &str,
+    monitor_map: &std::collections::HashMap<String, crate::monitor::Monitor>,
+) -> Result<(usize, &'c crate::config::MonitorGroup), crate::errors::SelectProfileError> {
+    let (index, monitor_group): (usize, &crate::config::MonitorGroup) = config
+        .profile(name)
+        .ok_or_else(|| crate::errors::ProfileNotFoundError::new(name.to_string()))?;
+    if !required_present(monitor_group, |monitor_id| {
+        monitor_map.contains_key(monitor_id)
+    }) {
+        return Err(
+            crate::errors::SelectProfileError::ProfileMonitorsNotPresentError(
+                crate::errors::ProfileMonitorsNotPresentError::new(
+                    name.to_string(),
+                    monitor_group
+                        .required()
+                        .filter(|monitor_id| !monitor_map.contains_key(*monitor_id))
+                        .cloned()
+                        .collect(),
+                ),
+            ),
+        );
+    }
+    println!("Using {}!", monitor_group.label(index));
+    Ok((index, monitor_group))
+}
+
+// Next matching monitor group in file order after the last applied one.
+pub(crate) fn next<'c>(
+    matching: &[(usize, &'c crate::config::MonitorGroup)],
+    last: Option<&str>,
+) -> Option<(usize, &'c crate::config::MonitorGroup)> {
+    let mut in_file_order: Vec<(usize, &crate::config::MonitorGroup)> = matching.to_vec();
+    in_file_order.sort_by_key(|(index, _)| *index);
+
+    // Position of the last applied one, if it still matches.
+    let position: Option<usize> = last.and_then(|last| {
+        in_file_order
+            .iter()
+            .position(|(index, monitor_group)| monitor_group.key(*index) == last)
+    });
+
+    let next: Option<(usize, &crate::config::MonitorGroup)> = match position {
+        Some(position) => in_file_order
+            .get((position + 1) % in_file_order.len())
+            .copied(),
+        None => in_file_order.first().copied(),
+    };
+    if let Some((index, monitor_group)) = next {
+        println!("Cycling to {}!", monitor_group.label(index));
+    }
+    next
+}
+
+// Profiles of matching monitor groups.
+pub(crate) fn profiles(
+    matching: &[(usize, &crate::config::MonitorGroup)],
+) -> Vec<crate::control::Profile> {
+    matching
+        .iter()
+        .map(|(index, monitor_group)| crate::control::Profile {
+            key: monitor_group.key(*index),
+            description: monitor_group.description.clone(),
+        })
+        .collect()
+}
+
+// Print matching profiles.
+pub(crate) fn print_profiles(profiles: &[crate::control::Profile]) {
+    if profiles.is_empty() {
+        println!("No matching profiles!");
+        return;
+    }
+    println!("Matching profiles:");
+    for profile in profiles {
+        match &profile.description {
+            Some(description) => {
+                println!("\t {}: {}", crate::config::label(&profile.key), description)
+            }
+            None => println!("\t {}", crate::config::label(&profile.key)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Config with monitor groups from TOML.
+    fn config(toml: &str) -> crate::config::Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    // Indexes of monitor groups sorted with connected monitors.
+    fn sorted(config: &crate::config::Config, connected: &[&str]) -> Vec<usize> {
+        let mut monitor_groups: Vec<(usize, &crate::config::MonitorGroup)> =
+            config.monitor_groups().enumerate().collect();
+        super::sort(config, &mut monitor_groups, |monitor_id| {
+            connected.contains(&monitor_id)
+        });
+        monitor_groups.iter().map(|(index, _)| *index).collect()
+    }
+
+    #[test]
+    fn optional_monitors_do_not_have_to_be_connected() {
+        let config: crate::config::Config = config(
+            r#"
+            monitor_groups = [["A", { id = "B", optional = true }]]
+            "#,
+        );
+        let monitor_group: &crate::config::MonitorGroup = config.monitor_groups().next().unwrap();
+        assert!(super::matches(monitor_group, &["A"]));
+        assert!(super::matches(monitor_group, &["A", "B"]));
+        assert!(!super::matches(monitor_group, &["B"]));
+    }
+
+    #[test]
+    fn group_of_only_optional_monitors_needs_one_of_them() {
+        let config: crate::config::Config = config(
+            r#"
+            monitor_groups = [[{ id = "A", optional = true }, { id = "B", optional = true }]]
+            "#,
+        );
+        let monitor_group: &crate::config::MonitorGroup = config.monitor_groups().next().unwrap();
+        assert!(super::matches(monitor_group, &["B"]));
+        assert!(!super::matches(monitor_group, &["C"]));
+        assert!(!super::matches(monitor_group, &[]));
+    }
+
+    #[test]
+    fn exact_group_allows_missing_optional_monitors() {
+        let config: crate::config::Config = config(
+            r#"
+            monitor_groups = [{ monitors = ["A", { id = "B", optional = true }], exact = true }]
+            "#,
+        );
+        let monitor_group: &crate::config::MonitorGroup = config.monitor_groups().next().unwrap();
+        assert!(super::matches(monitor_group, &["A"]));
+        assert!(super::matches(monitor_group, &["A", "B"]));
+        assert!(!super::matches(monitor_group, &["A", "C"]));
+    }
+
+    #[test]
+    fn sort_keeps_file_order_for_first() {
+        let config: crate::config::Config = config(
+            r#"
+            monitor_groups = [["A"], ["A", "B"], { monitors = ["A"], priority = 1 }]
+            "#,
+        );
+        assert_eq!(sorted(&config, &["A", "B"]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn sort_by_covered_monitors_then_priority_then_file_order() {
+        let config: crate::config::Config = config(
+            r#"
+            selection = "score"
+            monitor_groups = [
+                ["A"],
+                ["A", "B"],
+                { monitors = ["A"], priority = 1 },
+                ["A", "C"],
+                ["B"],
+            ]
+            "#,
+        );
+        assert_eq!(sorted(&config, &["A", "B"]), vec![1, 2, 0, 3, 4]);
+    }
+
+    #[test]
+    fn preferred_covers_the_most_monitors() {
+        let config: crate::config::Config = config(
+            r#"
+            selection = "score"
+            monitor_groups = [["A"], { monitors = ["B"], priority = 1 }, ["A", "C"]]
+            "#,
+        );
+        assert_eq!(super::preferred(&config), Some(2));
+    }
+
+    #[test]
+    fn next_cycles_in_file_order() {
+        let config: crate::config::Config = config(
+            r#"
+            monitor_groups = [["A"], ["B"]]
+            profiles = [{ name = "both", monitors = ["A", "B"] }]
+            "#,
+        );
+        let monitor_groups: Vec<&crate::config::MonitorGroup> = config.monitor_groups().collect();
+        // Matching in order of preference, not in file order.
+        let matching: Vec<(usize, &crate::config::MonitorGroup)> = vec![
+            (2, monitor_groups[2]),
+            (0, monitor_groups[0]),
+            (1, monitor_groups[1]),
+        ];
+        let next = |last: Option<&str>| super::next(&matching, last).map(|(index, _)| index);
+        assert_eq!(next(None), Some(0));
+        assert_eq!(next(Some("#1")), Some(1));
+        assert_eq!(next(Some("#2")), Some(2));
+        assert_eq!(next(Some("both")), Some(0));
+        // Last applied one does not match anymore.
+        assert_eq!(next(Some("gone")), Some(0));
+        assert_eq!(super::next(&[], Some("#1")).map(|(index, _)| index), None);
+    }
+}
@@ -0,0 +1,74 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Structure for snapshot of screen configuration.
+pub(crate) struct Snapshot<'a> {
+    // Connection to X server.
+    connection: &'a crate::connection::Connection,
+    // CRTC configs by CRTC id.
+    crtc_configs: Vec<(u32, crate::crtc_config::CrtcConfig)>,
+    // Primary output.
+    primary: u32,
+    // Screen size.
+    screen_width: u16,
+    screen_height: u16,
+    screen_width_mm: u32,
+    screen_height_mm: u32,
+}
+
+// Methods for snapshot.
+impl<'a> Snapshot<'a> {
+    // Take a snapshot of current screen configuration.
+    pub(crate) fn new(
+        connection: &'a crate::connection::Connection,
+    ) -> Result<Self, x11rb::errors::ReplyError> {
+        let mut crtc_configs: Vec<(u32, crate::crtc_config::CrtcConfig)> = Vec::new();
+        for crtc in connection.crtcs()? {
+            crtc_configs.push((crtc, connection.get_crtc_config(crtc)?));
+        }
+        let (screen_width, screen_height): (u16, u16) = connection.screen_size()?;
+        let (screen_width_mm, screen_height_mm): (u32, u32) = connection.screen_size_mm()?;
+        Ok(Snapshot {
+            connection,
+            crtc_configs,
+            primary: connection.get_output_primary()?,
+            screen_width,
+            screen_height,
+            screen_width_mm,
+            screen_height_mm,
+        })
+    }
+
+    // Restore screen configuration from snapshot.
+    pub(crate) fn restore(&self) -> Result<&Self, crate::errors::SetCrtcConfigError> {
+        println!("Restoring previous configuration...");
+
+        // Disable CRTCs that differ from snapshot, so that screen can be resized.
+        let disabled: crate::crtc_config::CrtcConfig = crate::crtc_config::CrtcConfig::disabled();
+        for (crtc, crtc_config) in &self.crtc_configs {
+            if !self.connection.get_crtc_config(*crtc)?.equals(crtc_config) {
+                self.connection.set_crtc_config(*crtc, &disabled, *crtc)?;
+            }
+        }
+
+        // Restore screen size.
+        self.connection.set_screen_size_mm(
+            self.screen_width,
+            self.screen_height,
+            self.screen_width_mm,
+            self.screen_height_mm,
+        )?;
+
+        // Restore CRTCs that were enabled.
+        for (crtc, crtc_config) in &self.crtc_configs {
+            if !crtc_config.is_disabled() {
+                self.connection.set_crtc_config(*crtc, crtc_config, *crtc)?;
+            }
+        }
+
+        // Restore primary output.
+        self.connection.set_output_primary(self.primary)?;
+
+        Ok(self)
+    }
+}
@@ -0,0 +1,30 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Path to file with last applied profile.
+fn last_profile_path() -> Option<std::path::PathBuf> {
+    // State directory from XDG_STATE_HOME or its default under HOME.
+    let state_home: std::path::PathBuf = match std::env::var_os("XDG_STATE_HOME") {
+        Some(state_home) if !state_home.is_empty() => std::path::PathBuf::from(state_home),
+        _ => std::path::PathBuf::from(std::env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(state_home.join("x11_edid_auto").join("last_profile"))
+}
+
+// Read last applied profile.
+pub(crate) fn read_last_profile() -> Option<String> {
+    let last_profile: String = std::fs::read_to_string(last_profile_path()?).ok()?;
+    Some(last_profile.trim().to_string())
+}
+
+// Write last applied profile.
+pub(crate) fn write_last_profile(key: &str) -> Result<(), std::io::Error> {
+    let path: std::path::PathBuf = match last_profile_path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    std::fs::write(path, format!("{}\n", key))
+}
@@ -0,0 +1,251 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Name of user unit.
+const UNIT_NAME: &str = "x11_edid_auto.service";
+
+// Seconds without watchdog ping before systemd restarts watch mode.
+const WATCHDOG_SEC: u32 = 30;
+
+// Watchdog to ping during long operations, with interval and time of last ping.
+static KEEP_ALIVE: std::sync::Mutex<Option<(Notifier, std::time::Duration, std::time::Instant)>> =
+    std::sync::Mutex::new(None);
+
+// Structure for sending notifications to systemd.
+pub(crate) struct Notifier {
+    // Socket for sending.
+    socket: std::os::unix::net::UnixDatagram,
+    // Address of systemd's notify socket.
+    address: std::os::unix::net::SocketAddr,
+}
+
+// Methods for notifier.
+impl Notifier {
+    // Notifier for socket in $NOTIFY_SOCKET, if there is one.
+    pub(crate) fn from_env() -> Result<Option<Self>, std::io::Error> {
+        let path: std::ffi::OsString = match std::env::var_os("NOTIFY_SOCKET") {
+            Some(path) if !path.is_empty() => path,
+            _ => return Ok(None),
+        };
+        // Socket starting with @ is in abstract namespace.
+        let address: std::os::unix::net::SocketAddr =
+            match std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).strip_prefix(b"@") {
+                Some(name) => {
+                    <std::os::unix::net::SocketAddr as std::os::linux::net::SocketAddrExt>::from_abstract_name(name)?
+                }
+                None => std::os::unix::net::SocketAddr::from_pathname(path)?,
+            };
+        Ok(Some(Notifier {
+            socket: std::os::unix::net::UnixDatagram::unbound()?,
+            address,
+        }))
+    }
+
+    // Notifier sending to the same socket.
+    pub(crate) fn try_clone(&self) -> Result<Self, std::io::Error> {
+        Ok(Notifier {
+            socket: self.socket.try_clone()?,
+            address: self.address.clone(),
+        })
+    }
+
+    // Send state, only printing errors.
+    pub(crate) fn notify(&self, state: &str) {
+        if let Err(error) = self.socket.send_to_addr(state.as_bytes(), &self.address) {
+            crate::log::error(&error);
+        }
+    }
+}
+
+// Interval for watchdog pings, half of what systemd expects, if watchdog is enabled.
+pub(crate) fn watchdog_interval() -> Option<std::time::Duration> {
+    // Watchdog might be meant for another process.
+    if let Ok(pid) = std::env::var("WATCHDOG_PID") {
+        if pid.parse::<u32>().ok() != Some(std::process::id()) {
+            return None;
+        }
+    }
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    if usec == 0 {
+        return None;
+    }
+    Some(std::time::Duration::from_micros(usec) / 2)
+}
+
+// Ping watchdog with notifier in every interval during long operations from now on.
+pub(crate) fn enable_keep_alive(notifier: Notifier, interval: std::time::Duration) {
+    if let Ok(mut keep_alive) = KEEP_ALIVE.lock() {
+        *keep_alive = Some((notifier, interval, std::time::Instant::now()));
+    }
+}
+
+// Ping watchdog during long operation, like running hooks, if interval has passed.
+pub(crate) fn keep_alive() {
+    if let Ok(mut keep_alive) = KEEP_ALIVE.lock() {
+        if let Some((notifier, interval, pinged)) = keep_alive.as_mut() {
+            if pinged.elapsed() >= *interval {
+                notifier.notify("WATCHDOG=1");
+                *pinged = std::time::Instant::now();
+            }
+        }
+    }
+}
+
+// User unit that runs watch mode with config file.
+fn unit(executable: &std::path::Path, config_file_path: &std::path::Path) -> String {
+    format!(
+        "[Unit]
+Description=Automatic monitor configuration based on EDID
+PartOf=graphical-session.target
+After=graphical-session.target
+
+[Service]
+Type=notify
+NotifyAccess=main
+ExecStart={} watch --systemd {}
+ExecReload=/bin/kill -HUP $MAINPID
+Restart=on-failure
+WatchdogSec={}
+
+[Install]
+WantedBy=graphical-session.target
+",
+        quote(executable, false),
+        quote(config_file_path, true),
+        WATCHDOG_SEC
+    )
+}
+
+// Path quoted for command line of unit, with specifiers escaped.
+// Variables are only expanded in arguments, not in executable.
+fn quote(path: &std::path::Path, is_argument: bool) -> String {
+    let mut quoted: String = String::from("\"");
+    for byte in std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()) {
+        match byte {
+            b'\\' => quoted.push_str("\\\\"),
+            b'"' => quoted.push_str("\\\""),
+            b'%' => quoted.push_str("%%"),
+            b'$' if is_argument => quoted.push_str("$$"),
+            // Control characters and bytes of non-ASCII characters are escaped in hex.
+            0x20..=0x7e => quoted.push(char::from(*byte)),
+            _ => quoted.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Can path be executable of unit? Systemd refuses quotes, backslashes and control characters in it.
+fn is_valid_executable(path: &std::path::Path) -> bool {
+    std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str())
+        .iter()
+        .all(|byte| !matches!(byte, b'"' | b'\'' | b'\\' | 0x00..=0x1f | 0x7f))
+}
+
+// Write user unit for watch mode, or only print it on dry run.
+pub(crate) fn install_service(
+    arguments: &crate::arguments::Arguments,
+) -> Result<(), std::io::Error> {
+    let executable: std::path::PathBuf = std::env::current_exe()?;
+    if !is_valid_executable(&executable) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("systemd does not allow executable {:?}", executable),
+        ));
+    }
+    let unit: String = unit(
+        &executable,
+        &std::fs::canonicalize(&arguments.config_file_path)?,
+    );
+    if arguments.dry_run {
+        print!("{}", unit);
+        return Ok(());
+    }
+
+    // Units are in $XDG_CONFIG_HOME/systemd/user or ~/.config/systemd/user.
+    let directory: std::path::PathBuf = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(config_home) if !config_home.is_empty() => std::path::PathBuf::from(config_home),
+        _ => std::path::PathBuf::from(std::env::var_os("HOME").unwrap_or_default()).join(".config"),
+    }
+    .join("systemd")
+    .join("user");
+    std::fs::create_dir_all(&directory)?;
+    let path: std::path::PathBuf = directory.join(UNIT_NAME);
+    std::fs::write(&path, unit)?;
+    println!("Wrote {:?}, enable it with:", path);
+    println!("\t systemctl --user daemon-reload");
+    println!("\t systemctl --user enable --now {}", UNIT_NAME);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn notifier_sends_to_notify_socket() {
+        let directory: std::path::PathBuf =
+            std::env::temp_dir().join(format!("x11_edid_auto-test-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path: std::path::PathBuf = directory.join("notify");
+        let _ = std::fs::remove_file(&path);
+        let socket: std::os::unix::net::UnixDatagram =
+            std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+        socket
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+
+        std::env::set_var("NOTIFY_SOCKET", &path);
+        let notifier: super::Notifier = super::Notifier::from_env().unwrap().unwrap();
+        notifier.notify("READY=1\nSTATUS=Applied \"work\"");
+
+        let mut buffer: [u8; 256] = [0; 256];
+        let length: usize = socket.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..length], b"READY=1\nSTATUS=Applied \"work\"");
+
+        // Watchdog is pinged during long operations only after interval.
+        super::enable_keep_alive(notifier.try_clone().unwrap(), std::time::Duration::ZERO);
+        super::keep_alive();
+        let length: usize = socket.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..length], b"WATCHDOG=1");
+        super::enable_keep_alive(notifier, std::time::Duration::from_secs(3600));
+        super::keep_alive();
+        socket.set_nonblocking(true).unwrap();
+        assert_eq!(
+            socket.recv(&mut buffer).unwrap_err().kind(),
+            std::io::ErrorKind::WouldBlock
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn quote_escapes_for_systemd() {
+        assert_eq!(
+            super::quote(std::path::Path::new("/usr/bin/x11_edid_auto"), false),
+            "\"/usr/bin/x11_edid_auto\""
+        );
+        assert_eq!(
+            super::quote(
+                std::path::Path::new("/home/me/my \"100%\" $HOME\\config.toml"),
+                true
+            ),
+            "\"/home/me/my \\\"100%%\\\" $$HOME\\\\config.toml\""
+        );
+        assert_eq!(
+            super::quote(std::path::Path::new("/home/m\u{e4}/a\nb"), true),
+            "\"/home/m\\xc3\\xa4/a\\x0ab\""
+        );
+        assert_eq!(
+            super::quote(std::path::Path::new("/opt/$PATH%/x11_edid_auto"), false),
+            "\"/opt/$PATH%%/x11_edid_auto\""
+        );
+    }
+
+    #[test]
+    fn executable_with_special_characters_is_invalid() {
+        assert!(super::is_valid_executable(std::path::Path::new(
+            "/home/m\u{e4} 100%/$bin/x11_edid_auto"
+        )));
+        for path in ["/a\"b", "/a'b", "/a\\b", "/a\nb"] {
+            assert!(!super::is_valid_executable(std::path::Path::new(path)));
+        }
+    }
+}
@@ -0,0 +1,751 @@
+// Forbid unsafe code.
+#![forbid(unsafe_code)]
+
+// Length of window for limiting applies.
+const FLAP_WINDOW: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Structure for limiting how often monitor groups are applied.
+struct FlapGuard {
+    // Maximum number of applies in window, 0 for no limit.
+    max_applies: usize,
+    // Times of applies in window.
+    applies: std::collections::VecDeque<std::time::Instant>,
+}
+
+// Methods for flap guard.
+impl FlapGuard {
+    // Create a new flap guard with maximum number of applies in window.
+    fn new(max_applies: usize) -> Self {
+        FlapGuard {
+            max_applies,
+            applies: std::collections::VecDeque::new(),
+        }
+    }
+
+    // How long to wait before next apply is allowed, if at all.
+    fn delay(&mut self) -> Option<std::time::Duration> {
+        // Forget applies that are out of window.
+        while self
+            .applies
+            .front()
+            .is_some_and(|applied| applied.elapsed() >= FLAP_WINDOW)
+        {
+            self.applies.pop_front();
+        }
+        if self.max_applies == 0 || self.applies.len() < self.max_applies {
+            return None;
+        }
+        self.applies
+            .front()
+            .map(|applied| FLAP_WINDOW.saturating_sub(applied.elapsed()))
+    }
+
+    // Record an apply.
+    fn record(&mut self) {
+        self.applies.push_back(std::time::Instant::now());
+    }
+}
+
+// Events that watch mode acts on.
+enum Event {
+    // Connected monitors or their EDIDs might have changed.
+    MonitorsChanged,
+    // Config should be reloaded.
+    Reload,
+    // Connection for listening RandR events was lost.
+    ConnectionLost(x11rb::errors::ConnectionError),
+    // Request from control socket, with channel for response.
+    Control(
+        crate::control::Request,
+        std::sync::mpsc::Sender<crate::control::Response>,
+    ),
+    // Time to tell systemd that watch mode is alive.
+    Watchdog,
+}
+
+// Monitor group that was last applied.
+struct Applied {
+    // Connected monitors it was applied for.
+    monitors: Vec<String>,
+    // Key of monitor group, if one was selected.
+    key: Option<String>,
+}
+
+// Structure for watch mode.
+pub(crate) struct Watcher<'r> {
+    // Command line arguments.
+    arguments: &'r crate::arguments::Arguments,
+    // Current config.
+    config: crate::config::Config,
+    // Monitor group that was last applied.
+    applied: Option<Applied>,
+    // Connected monitors that were last seen, also while paused.
+    monitors: Option<Vec<String>>,
+    // Limit for how often monitor groups are applied.
+    flap_guard: FlapGuard,
+    // Is automatic applying paused?
+    paused: bool,
+    // Should monitor group be selected again, even if connected monitors did not change?
+    reselect: bool,
+    // Signals of D-Bus service, if it is being served.
+    #[cfg(feature = "dbus")]
+    dbus: Option<crate::dbus::Signals>,
+    // Notifier for systemd, when running as its service.
+    systemd: Option<crate::systemd::Notifier>,
+}
+
+// Methods for watch mode.
+impl<'r> Watcher<'r> {
+    // Create a new watcher with arguments and config.
+    pub(crate) fn new(
+        arguments: &'r crate::arguments::Arguments,
+        config: crate::config::Config,
+    ) -> Self {
+        Watcher {
+            arguments,
+            flap_guard: FlapGuard::new(config.watch.max_applies_per_minute),
+            config,
+            applied: None,
+            monitors: None,
+            paused: false,
+            reselect: false,
+            #[cfg(feature = "dbus")]
+            dbus: None,
+            systemd: None,
+        }
+    }
+
+    // Time without events before acting on them.
+    fn settle(&self) -> std::time::Duration {
+        std::time::Duration::try_from_secs_f64(self.config.watch.settle).unwrap_or_default()
+    }
+
+    // Apply monitor group whenever connected monitors change or config is reloaded.
+    pub(crate) fn watch(mut self) -> Result<(), crate::errors::X11EDIDAutoError> {
+        // Connection for applying, that holds lock while watching.
+        let mut connection: crate::connection::Connection = crate::connection::Connection::new()?;
+        crate::lock::lock(&connection, self.config.lock, crate::lock::WATCH, false)?;
+
+        // Send events from RandR, SIGHUP, config file changes and control socket.
+        let (sender, receiver): (
+            std::sync::mpsc::Sender<Event>,
+            std::sync::mpsc::Receiver<Event>,
+        ) = std::sync::mpsc::channel();
+        let event_connection: crate::connection::Connection = crate::connection::Connection::new()?;
+        event_connection.select_randr_input()?;
+        spawn_randr_events(event_connection, sender.clone());
+        spawn_signals(sender.clone())?;
+        spawn_config_watch(&self.arguments.config_file_path, sender.clone())?;
+        if self.arguments.systemd {
+            self.systemd = crate::systemd::Notifier::from_env()?;
+            if let Some(interval) = crate::systemd::watchdog_interval() {
+                spawn_watchdog(interval, sender.clone());
+                if let Some(notifier) = &self.systemd {
+                    crate::systemd::enable_keep_alive(notifier.try_clone()?, interval);
+                }
+            }
+        }
+        // Without runtime directory, watching continues without control socket.
+        match crate::control::listen(sender.clone(), Event::Control) {
+            Ok(()) => {}
+            Err(crate::errors::ControlError::NoRuntimeDirError(error)) => crate::log::error(&error),
+            Err(error) => return Err(error.into()),
+        }
+
+        // D-Bus service is optional, so watching continues without it.
+        #[cfg(feature = "dbus")]
+        match crate::dbus::serve(sender, Event::Control) {
+            Ok(signals) => self.dbus = Some(signals),
+            Err(error) => crate::log::error(&error),
+        }
+
+        let mut ready: bool = false;
+        loop {
+            // Modes might have changed.
+            connection.refresh()?;
+
+            // Apply when connected monitors or selected monitor group change, unless paused.
+            let delay: Option<std::time::Duration> = match self.check(&connection) {
+                Ok(delay) => delay,
+                // Errors do not stop watching.
+                Err(error) => {
+                    crate::log::error(&error);
+                    None
+                }
+            };
+
+            // Tell systemd about current state, and that startup is done.
+            let status: String = format!("STATUS={}", self.status());
+            if ready {
+                self.notify_systemd(&status);
+            } else {
+                self.notify_systemd(&format!("READY=1\n{}", status));
+                ready = true;
+            }
+
+            // Wait for next event, or until delay is over.
+            println!("Waiting for monitor changes...");
+            let deadline: Option<std::time::Instant> =
+                delay.map(|delay| std::time::Instant::now() + delay);
+            let event: Option<Event> = loop {
+                let event: Event = match deadline {
+                    Some(deadline) => match receiver
+                        .recv_timeout(deadline.saturating_duration_since(std::time::Instant::now()))
+                    {
+                        Ok(event) => event,
+                        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => break None,
+                        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+                    },
+                    None => match receiver.recv() {
+                        Ok(event) => event,
+                        Err(_) => return Ok(()),
+                    },
+                };
+                // Watchdog does not need checking monitors again.
+                match event {
+                    Event::Watchdog => self.notify_systemd("WATCHDOG=1"),
+                    event => break Some(event),
+                }
+            };
+            // Delay is over.
+            let event: Event = match event {
+                Some(event) => event,
+                None => continue,
+            };
+
+            // Handle events, collecting changes until they settle.
+            let mut next: Option<Event> = Some(event);
+            let mut settling: bool = false;
+            while let Some(event) = next.take() {
+                match event {
+                    Event::MonitorsChanged => settling = true,
+                    Event::Reload => {
+                        self.reload();
+                        settling = true;
+                    }
+                    Event::ConnectionLost(error) => return Err(error.into()),
+                    // Requests are answered right away.
+                    Event::Control(request, responder) => {
+                        connection.refresh()?;
+                        let _ = responder.send(self.control(&connection, request));
+                    }
+                    Event::Watchdog => self.notify_systemd("WATCHDOG=1"),
+                }
+                if settling {
+                    next = receiver.recv_timeout(self.settle()).ok();
+                }
+            }
+        }
+    }
+
+    // Handle request from control socket.
+    fn control(
+        &mut self,
+        connection: &crate::connection::Connection,
+        request: crate::control::Request,
+    ) -> crate::control::Response {
+        println!("Got request {:?}", request);
+        match request {
+            crate::control::Request::Status => crate::control::Response {
+                profile: self
+                    .applied
+                    .as_ref()
+                    .and_then(|applied| applied.key.clone()),
+                monitors: self.monitors.clone(),
+                paused: Some(self.paused),
+                config: Some(crate::control::config_path(
+                    &self.arguments.config_file_path,
+                )),
+                ..crate::control::Response::ok()
+            },
+            crate::control::Request::List => match self.list(connection) {
+                Ok(profiles) => crate::control::Response {
+                    profiles: Some(profiles),
+                    ..crate::control::Response::ok()
+                },
+                Err(error) => crate::control::Response::error(error),
+            },
+            crate::control::Request::Monitors => {
+                match crate::apply::monitor_map(connection, &self.config) {
+                    Ok(monitor_map) => crate::control::Response {
+                        monitors: Some(sorted_monitors(&monitor_map)),
+                        ..crate::control::Response::ok()
+                    },
+                    Err(error) => crate::control::Response::error(error),
+                }
+            }
+            crate::control::Request::Apply { profile } => self.apply_now(
+                connection,
+                &crate::arguments::Command::Apply,
+                profile.as_deref(),
+            ),
+            crate::control::Request::Cycle => {
+                self.apply_now(connection, &crate::arguments::Command::Cycle, None)
+            }
+            crate::control::Request::Reload => {
+                if self.reload() {
+                    crate::control::Response::ok()
+                } else {
+                    crate::control::Response::error("Config is not valid, keeping old config!")
+                }
+            }
+            crate::control::Request::Pause => {
+                self.paused = true;
+                crate::control::Response {
+                    paused: Some(self.paused),
+                    ..crate::control::Response::ok()
+                }
+            }
+            crate::control::Request::Resume => {
+                self.paused = false;
+                crate::control::Response {
+                    paused: Some(self.paused),
+                    ..crate::control::Response::ok()
+                }
+            }
+        }
+    }
+
+    // Matching profiles for connected monitors.
+    fn list(
+        &self,
+        connection: &crate::connection::Connection,
+    ) -> Result<Vec<crate::control::Profile>, crate::errors::X11EDIDAutoError> {
+        let monitor_map: std::collections::HashMap<String, crate::monitor::Monitor> =
+            crate::apply::monitor_map(connection, &self.config)?;
+        Ok(crate::selection::profiles(&crate::selection::matching(
+            &self.config,
+            &monitor_map,
+        )))
+    }
+
+    // Apply monitor group selected with command and profile right away.
+    fn apply_now(
+        &mut self,
+        connection: &crate::connection::Connection,
+        command: &crate::arguments::Command,
+        profile: Option<&str>,
+    ) -> crate::control::Response {
+        let result: Result<Applied, crate::errors::X11EDIDAutoError> = (|| {
+            let monitor_map: std::collections::HashMap<String, crate::monitor::Monitor> =
+                crate::apply::monitor_map(connection, &self.config)?;
+            let monitor_group: (usize, &crate::config::MonitorGroup) = crate::apply::select(
+                &self.config,
+                &monitor_map,
+                command,
+                profile,
+            )
+            .inspect_err(|error| {
+                crate::apply::no_match(&self.config, &monitor_map, error, self.arguments.dry_run)
+            })?;
+            crate::apply::apply(
+                connection,
+                &self.config,
+                &monitor_map,
+                monitor_group,
+                self.arguments.dry_run,
+            )?;
+            Ok(Applied {
+                monitors: sorted_monitors(&monitor_map),
+                key: Some(monitor_group.1.key(monitor_group.0)),
+            })
+        })();
+        match result {
+            Ok(applied) => {
+                let response: crate::control::Response = crate::control::Response {
+                    profile: applied.key.clone(),
+                    ..crate::control::Response::ok()
+                };
+                self.notify_applied(&applied);
+                self.applied = Some(applied);
+                response
+            }
+            Err(error) => {
+                if !self.arguments.dry_run {
+                    crate::notification::failed(&self.config, &error);
+                }
+                crate::control::Response::error(error)
+            }
+        }
+    }
+
+    // Reload config, keeping the old one if new one is not valid.
+    // Returns whether config was reloaded.
+    fn reload(&mut self) -> bool {
+        match reload_config(&self.arguments.config_file_path) {
+            Some(config) => {
+                self.flap_guard.max_applies = config.watch.max_applies_per_minute;
+                self.config = config;
+                self.reselect = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Tell listeners when connected monitors change, and unless paused apply monitor group,
+    // if connected monitors differ from the ones it was last applied for,
+    // or if selected monitor group differs after config was reloaded.
+    // Returns delay when applying has to wait.
+    fn check(
+        &mut self,
+        connection: &crate::connection::Connection,
+    ) -> Result<Option<std::time::Duration>, crate::errors::X11EDIDAutoError> {
+        // Reselecting is pending until monitor group is applied.
+        let reselect: bool = self.reselect;
+        let monitor_map: std::collections::HashMap<String, crate::monitor::Monitor> =
+            crate::apply::monitor_map(connection, &self.config)?;
+
+        // Tell listeners about changed monitors, even when paused.
+        let monitors: Vec<String> = sorted_monitors(&monitor_map);
+        if self.monitors.as_ref() != Some(&monitors) {
+            println!("Connected monitors changed to {:?}!", monitors);
+            self.notify_monitors_changed(&monitors);
+            self.monitors = Some(monitors.clone());
+        }
+        if self.paused {
+            return Ok(None);
+        }
+
+        let monitors_changed: bool = self
+            .applied
+            .as_ref()
+            .map_or(true, |applied| applied.monitors != monitors);
+        if !monitors_changed && !reselect {
+            return Ok(None);
+        }
+
+        // Select monitor group.
+        let selected: Result<
+            (usize, &crate::config::MonitorGroup),
+            crate::errors::X11EDIDAutoError,
+        > = crate::apply::select(
+            &self.config,
+            &monitor_map,
+            &self.arguments.command,
+            self.arguments.profile.as_deref(),
+        );
+        let key: Option<String> = selected
+            .as_ref()
+            .ok()
+            .map(|(index, monitor_group)| monitor_group.key(*index));
+        if !monitors_changed {
+            if self
+                .applied
+                .as_ref()
+                .is_some_and(|applied| applied.key == key)
+            {
+                self.reselect = false;
+                return Ok(None);
+            }
+            println!("Selected monitor group changed to {:?}!", key);
+        }
+
+        // Do not apply too often.
+        if let Some(delay) = self.flap_guard.delay() {
+            println!("Applied too many times, delaying for {:?}...", delay);
+            return Ok(Some(delay));
+        }
+        self.flap_guard.record();
+        self.reselect = false;
+
+        // Failed apply is not retried until connected monitors or selected monitor group change.
+        let result: Result<(), crate::errors::X11EDIDAutoError> = match selected {
+            Ok(monitor_group) => crate::apply::apply(
+                connection,
+                &self.config,
+                &monitor_map,
+                monitor_group,
+                self.arguments.dry_run,
+            ),
+            Err(error) => {
+                crate::apply::no_match(&self.config, &monitor_map, &error, self.arguments.dry_run);
+                Err(error)
+            }
+        };
+        let applied: Applied = Applied { monitors, key };
+        match result {
+            Ok(()) => self.notify_applied(&applied),
+            Err(error) => {
+                crate::log::error(&error);
+                if !self.arguments.dry_run {
+                    crate::notification::failed(&self.config, &error);
+                }
+            }
+        }
+        self.applied = Some(applied);
+        Ok(None)
+    }
+
+    // Current state for systemd.
+    fn status(&self) -> String {
+        if self.paused {
+            return "Paused".to_string();
+        }
+        match self
+            .applied
+            .as_ref()
+            .and_then(|applied| applied.key.as_ref())
+        {
+            Some(key) => format!("Applied {}", key),
+            None => "No monitor group applied".to_string(),
+        }
+    }
+
+    // Send state to systemd, when running as its service.
+    fn notify_systemd(&self, state: &str) {
+        if let Some(systemd) = &self.systemd {
+            systemd.notify(state);
+        }
+    }
+
+    // Tell listeners that monitor group was applied.
+    #[cfg_attr(not(feature = "dbus"), allow(unused_variables))]
+    fn notify_applied(&self, applied: &Applied) {
+        #[cfg(feature = "dbus")]
+        if let (Some(dbus), Some(key)) = (&self.dbus, &applied.key) {
+            dbus.profile_applied(key, &applied.monitors);
+        }
+    }
+
+    // Tell listeners that connected monitors changed.
+    #[cfg_attr(not(feature = "dbus"), allow(unused_variables))]
+    fn notify_monitors_changed(&self, monitors: &[String]) {
+        #[cfg(feature = "dbus")]
+        if let Some(dbus) = &self.dbus {
+            dbus.monitors_changed(monitors);
+        }
+    }
+}
+
+// Load config again, none if it is not valid.
+fn reload_config(config_file_path: &str) -> Option<crate::config::Config> {
+    println!("Reloading config...");
+    match crate::config::Config::load(config_file_path) {
+        Ok(config) => {
+            println!("Reloaded config!");
+            Some(config)
+        }
+        Err(error) => {
+            crate::log::error(&error);
+            eprintln!("Keeping old config!");
+            None
+        }
+    }
+}
+
+// Send event for every RandR change event.
+fn spawn_randr_events(
+    connection: crate::connection::Connection,
+    sender: std::sync::mpsc::Sender<Event>,
+) {
+    std::thread::spawn(move || loop {
+        let event: Event = match connection.wait_for_randr_event() {
+            Ok(_) => Event::MonitorsChanged,
+            Err(error) => Event::ConnectionLost(error),
+        };
+        let stop: bool = matches!(event, Event::ConnectionLost(_));
+        if sender.send(event).is_err() || stop {
+            return;
+        }
+    });
+}
+
+// Send watchdog event in every interval.
+fn spawn_watchdog(interval: std::time::Duration, sender: std::sync::mpsc::Sender<Event>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(interval);
+        if sender.send(Event::Watchdog).is_err() {
+            return;
+        }
+    });
+}
+
+// Send reload event for every SIGHUP.
+fn spawn_signals(sender: std::sync::mpsc::Sender<Event>) -> Result<(), std::io::Error> {
+    let mut signals: signal_hook::iterator::Signals =
+        signal_hook::iterator::Signals::new([signal_hook::consts::SIGHUP])?;
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            if sender.send(Event::Reload).is_err() {
+                return;
+            }
+        }
+    });
+    Ok(())
+}
+
+// Send reload event whenever config file changes.
+fn spawn_config_watch(
+    config_file_path: &str,
+    sender: std::sync::mpsc::Sender<Event>,
+) -> Result<(), std::io::Error> {
+    // Watch directory of config file, so that replaced files are noticed too.
+    let path: &std::path::Path = std::path::Path::new(config_file_path);
+    let directory: &std::path::Path = match path.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => std::path::Path::new("."),
+    };
+    let file_name: std::ffi::OsString = path.file_name().unwrap_or_default().to_os_string();
+    let mut inotify: inotify::Inotify = inotify::Inotify::init()?;
+    inotify.watches().add(
+        directory,
+        inotify::WatchMask::CLOSE_WRITE | inotify::WatchMask::MOVED_TO | inotify::WatchMask::CREATE,
+    )?;
+    std::thread::spawn(move || {
+        let mut buffer: [u8; 4096] = [0; 4096];
+        while let Ok(events) = inotify.read_events_blocking(&mut buffer) {
+            if events
+                .into_iter()
+                .any(|event| event.name == Some(file_name.as_os_str()))
+                && sender.send(Event::Reload).is_err()
+            {
+                return;
+            }
+        }
+    });
+    Ok(())
+}
+
+// Wait until the most preferred or given monitor group matches connected monitors, or timeout.
+pub(crate) fn wait(
+    connection: &mut crate::connection::Connection,
+    arguments: &crate::arguments::Arguments,
+    config: &crate::config::Config,
+    timeout: std::time::Duration,
+) -> Result<(), crate::errors::X11EDIDAutoError> {
+    // Listen for RandR events.
+    connection.select_randr_input()?;
+
+    let start: std::time::Instant = std::time::Instant::now();
+    loop {
+        // Modes might have changed.
+        connection.refresh()?;
+        if is_satisfied(connection, arguments, config) {
+            return Ok(());
+        }
+
+        // Wait for next change until timeout.
+        let remaining: std::time::Duration = timeout.saturating_sub(start.elapsed());
+        if remaining.is_zero() {
+            println!("Timed out waiting for monitors, using what is available!");
+            return Ok(());
+        }
+        println!("Waiting for monitors for {:?}...", remaining);
+        if connection.wait_for_randr_event_timeout(remaining)? {
+            connection.wait_for_settle(std::cmp::min(
+                std::time::Duration::try_from_secs_f64(config.watch.settle).unwrap_or_default(),
+                timeout.saturating_sub(start.elapsed()),
+            ))?;
+        }
+    }
+}
+
+// Does the most preferred or given monitor group match connected monitors?
+fn is_satisfied(
+    connection: &crate::connection::Connection,
+    arguments: &crate::arguments::Arguments,
+    config: &crate::config::Config,
+) -> bool {
+    let monitor_map: std::collections::HashMap<String, crate::monitor::Monitor> =
+        match crate::apply::monitor_map(connection, config) {
+            Ok(monitor_map) => monitor_map,
+            Err(_) => return false,
+        };
+    match &arguments.profile {
+        Some(name) => config.profile(name).is_some_and(|(_, monitor_group)| {
+            crate::selection::required_present(monitor_group, |monitor_id| {
+                monitor_map.contains_key(monitor_id)
+            })
+        }),
+        None => {
+            crate::selection::matching(config, &monitor_map)
+                .first()
+                .map(|(index, _)| *index)
+                == crate::selection::preferred(config)
+        }
+    }
+}
+
+// Connected monitors in stable order.
+fn sorted_monitors(
+    monitor_map: &std::collections::HashMap<String, crate::monitor::Monitor>,
+) -> Vec<String> {
+    let mut monitors: Vec<String> = monitor_map.keys().cloned().collect();
+    monitors.sort();
+    monitors
+}
+
+#[cfg(test)]
+mod tests {
+    // Reload config written to temporary file.
+    fn reload(name: &str, contents: &str) -> Option<crate::config::Config> {
+        let path: std::path::PathBuf = std::env::temp_dir().join(format!(
+            "x11_edid_auto_{}_{}.toml",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, contents).unwrap();
+        let config: Option<crate::config::Config> = super::reload_config(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        config
+    }
+
+    #[test]
+    fn reload_uses_valid_config() {
+        let config: crate::config::Config = reload(
+            "valid",
+            "monitor_groups = [[\"AAA:0001:00000001\"]]\n[watch]\nmax_applies_per_minute = 2\n",
+        )
+        .unwrap();
+        assert_eq!(config.watch.max_applies_per_minute, 2);
+    }
+
+    #[test]
+    fn reload_keeps_old_config_when_new_is_not_valid() {
+        assert!(reload("syntax", "monitor_groups = [[\"AAA:0001:00000001\"]").is_none());
+        assert!(reload("empty", "").is_none());
+        assert!(reload(
+            "mirror",
+            "monitor_groups = [{ monitors = [\"A\"], mirror = [\"B\"] }]\n",
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn reload_keeps_old_config_when_file_is_gone() {
+        assert!(super::reload_config("/nonexistent/x11_edid_auto/config.toml").is_none());
+    }
+
+    #[test]
+    fn flap_guard_without_limit_never_delays() {
+        let mut flap_guard: super::FlapGuard = super::FlapGuard::new(0);
+        for _ in 0..100 {
+            assert_eq!(flap_guard.delay(), None);
+            flap_guard.record();
+        }
+    }
+
+    #[test]
+    fn flap_guard_delays_after_limit() {
+        let mut flap_guard: super::FlapGuard = super::FlapGuard::new(2);
+        assert_eq!(flap_guard.delay(), None);
+        flap_guard.record();
+        assert_eq!(flap_guard.delay(), None);
+        flap_guard.record();
+        let delay: std::time::Duration = flap_guard.delay().unwrap();
+        assert!(delay > std::time::Duration::ZERO && delay <= super::FLAP_WINDOW);
+    }
+
+    #[test]
+    fn flap_guard_forgets_applies_out_of_window() {
+        let mut flap_guard: super::FlapGuard = super::FlapGuard::new(1);
+        match std::time::Instant::now().checked_sub(super::FLAP_WINDOW) {
+            Some(applied) => flap_guard.applies.push_back(applied),
+            // Clock has not run for a whole window yet.
+            None => return,
+        }
+        assert_eq!(flap_guard.delay(), None);
+        assert!(flap_guard.applies.is_empty());
+    }
+}